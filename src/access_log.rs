@@ -0,0 +1,163 @@
+use crate::utils::fields::value_to_text;
+use crate::utils::time::record_time_or_now;
+use crate::{Format, FormatOptions, LogInfo};
+use serde_json::Value;
+
+const COMMON: &str =
+    r#"$remote_addr - $remote_user [$time_local] "$request" $status $body_bytes_sent"#;
+const COMBINED: &str = r#"$remote_addr - $remote_user [$time_local] "$request" $status $body_bytes_sent "$http_referer" "$http_user_agent""#;
+
+fn meta_text(info: &LogInfo, key: &str) -> Option<String> {
+    match info.meta.get(key)? {
+        Value::Null => None,
+        value => Some(value_to_text(value)),
+    }
+}
+
+/// Resolves an nginx variable name against the request meta.
+fn variable(info: &LogInfo, name: &str) -> Option<String> {
+    match name {
+        "remote_addr" | "remote_user" | "status" => meta_text(info, name),
+        "request_method" => meta_text(info, "method"),
+        "request_uri" | "uri" => meta_text(info, "path"),
+        "server_protocol" => meta_text(info, "protocol"),
+        "body_bytes_sent" | "bytes_sent" => meta_text(info, "bytes"),
+        "http_referer" => meta_text(info, "referer"),
+        "http_user_agent" => meta_text(info, "user_agent"),
+        "request" => {
+            let method = meta_text(info, "method")?;
+            let path = meta_text(info, "path").unwrap_or_else(|| "-".to_string());
+            let protocol = meta_text(info, "protocol").unwrap_or_else(|| "HTTP/1.1".to_string());
+            Some(format!("{} {} {}", method, path, protocol))
+        }
+        "request_time" => {
+            let millis = info.meta.get("duration_ms")?.as_f64()?;
+            Some(format!("{:.3}", millis / 1000.0))
+        }
+        "time_local" => Some(
+            record_time_or_now(info, "timestamp")
+                .format("%d/%b/%Y:%H:%M:%S %z")
+                .to_string(),
+        ),
+        "time_iso8601" => Some(
+            record_time_or_now(info, "timestamp")
+                .format("%Y-%m-%dT%H:%M:%S%:z")
+                .to_string(),
+        ),
+        "msec" => {
+            let millis = record_time_or_now(info, "timestamp").timestamp_millis();
+            Some(format!("{}.{:03}", millis / 1000, millis % 1000))
+        }
+        "level" => Some(info.level.clone()),
+        "message" => Some(info.message.clone()),
+        _ => meta_text(info, name),
+    }
+}
+
+/// Escapes quotes, backslashes and control characters the way nginx does (`\xHH`).
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '"' || c == '\\' || c.is_ascii_control() {
+            escaped.push_str(&format!("\\x{:02X}", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Fills a `log_format`-style template, replacing `$name` and `${name}` with the
+/// variable's value, or `-` when it is missing.
+fn render(template: &str, info: &LogInfo) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let (name, consumed) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+
+        if name.is_empty() {
+            output.push('$');
+        } else {
+            let value = variable(info, name).unwrap_or_else(|| "-".to_string());
+            output.push_str(&escape(&value));
+        }
+        rest = &after[consumed..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+pub fn access_log() -> Format {
+    Format::new(|mut info: LogInfo, opts: FormatOptions| {
+        let get = |key: &str| opts.as_ref().and_then(|o| o.get(key));
+
+        let template = match (get("template"), get("format").map(String::as_str)) {
+            (Some(template), _) => template.as_str(),
+            (None, Some("common")) => COMMON,
+            _ => COMBINED,
+        };
+
+        info.message = render(template, &info);
+        Some(info)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> LogInfo {
+        LogInfo::new("info", "request handled")
+            .add_meta("timestamp", "2024-08-27T02:39:15Z")
+            .add_meta("method", "GET")
+            .add_meta("path", "/search?q=\"rust\"")
+            .add_meta("status", 200)
+            .add_meta("bytes", 5120)
+            .add_meta("remote_addr", "10.0.0.1")
+            .add_meta("user_agent", "curl/8.0")
+            .add_meta("duration_ms", 12.5)
+    }
+
+    #[test]
+    fn test_access_log_formats() {
+        let result = access_log().transform(request(), None).unwrap();
+        println!("{}", result.message);
+        assert_eq!(
+            result.message,
+            r#"10.0.0.1 - - [27/Aug/2024:02:39:15 +0000] "GET /search?q=\x22rust\x22 HTTP/1.1" 200 5120 "-" "curl/8.0""#
+        );
+
+        let common = access_log().with_option("format", "common");
+        let result = common.transform(request(), None).unwrap();
+        assert_eq!(
+            result.message,
+            r#"10.0.0.1 - - [27/Aug/2024:02:39:15 +0000] "GET /search?q=\x22rust\x22 HTTP/1.1" 200 5120"#
+        );
+    }
+
+    #[test]
+    fn test_access_log_template() {
+        let formatter = access_log().with_option(
+            "template",
+            "$request_method ${status}ms=$request_time $$ $upstream",
+        );
+
+        let result = formatter.transform(request(), None).unwrap();
+        assert_eq!(result.message, "GET 200ms=0.013 $$ -");
+    }
+}
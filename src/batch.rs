@@ -0,0 +1,178 @@
+use crate::{FormatOptions, LogInfo};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+type BoxedBatchFn = Arc<dyn Fn(Vec<LogInfo>, FormatOptions) -> Option<String> + Send + Sync>;
+
+/// A format that renders many log infos into a single body, e.g. a JSON array
+/// for an HTTP intake endpoint.
+pub struct BatchFormat {
+    pub batch_fn: BoxedBatchFn,
+    pub options: FormatOptions,
+}
+
+impl BatchFormat {
+    pub fn new<F>(batch_fn: F) -> Self
+    where
+        F: Fn(Vec<LogInfo>, FormatOptions) -> Option<String> + Send + Sync + 'static,
+    {
+        BatchFormat {
+            batch_fn: Arc::new(batch_fn),
+            options: None,
+        }
+    }
+
+    pub fn transform_batch(&self, infos: Vec<LogInfo>, opts: FormatOptions) -> Option<String> {
+        let merged_opts = self.merge_options(opts);
+        (self.batch_fn)(infos, merged_opts)
+    }
+
+    pub fn with_option(mut self, key: &str, value: &str) -> Self {
+        self.options
+            .get_or_insert_with(HashMap::new)
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    fn merge_options(&self, opts: FormatOptions) -> FormatOptions {
+        let mut final_opts = self.options.clone().unwrap_or_default();
+        if let Some(incoming_opts) = opts {
+            final_opts.extend(incoming_opts);
+        }
+        Some(final_opts)
+    }
+}
+
+impl Clone for BatchFormat {
+    fn clone(&self) -> Self {
+        BatchFormat {
+            batch_fn: Arc::clone(&self.batch_fn),
+            options: self.options.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for BatchFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BatchFormat")
+            .field("batch_fn", &"<batch_fn>")
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+/// Splits the records into bodies rendered with the given batch format, each with at
+/// most `max_records` records and `max_bytes` bytes. A record too large to fit in
+/// `max_bytes` on its own is sent in a batch of one.
+///
+/// Sizes are checked by rendering, assuming that adding a record to a batch grows the
+/// body by no more than the size of that record rendered on its own, which holds for
+/// arrays and envelopes.
+pub fn split_batches(
+    records: Vec<LogInfo>,
+    format: &BatchFormat,
+    opts: FormatOptions,
+) -> Vec<String> {
+    let get = |key: &str| {
+        opts.as_ref()
+            .and_then(|o| o.get(key))
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|n| *n > 0)
+    };
+    let max_records = get("max_records").unwrap_or(usize::MAX);
+    let max_bytes = get("max_bytes").unwrap_or(usize::MAX);
+
+    let render = |batch: &[LogInfo]| format.transform_batch(batch.to_vec(), None);
+
+    let mut bodies = Vec::new();
+    let mut pending: Vec<LogInfo> = Vec::new();
+    // An upper bound for the size of `pending` rendered, exact after each render
+    let mut pending_bytes = 0;
+
+    for record in records {
+        if pending.len() >= max_records {
+            bodies.extend(render(&pending));
+            pending.clear();
+        }
+
+        if pending.is_empty() {
+            pending_bytes = render(std::slice::from_ref(&record)).map_or(0, |b| b.len());
+            pending.push(record);
+            continue;
+        }
+
+        let single = render(std::slice::from_ref(&record)).map_or(0, |b| b.len());
+        pending.push(record);
+
+        if pending_bytes + single <= max_bytes {
+            pending_bytes += single;
+            continue;
+        }
+
+        // Close to the limit, so check the exact size
+        let exact = render(&pending).map_or(0, |b| b.len());
+        if exact <= max_bytes {
+            pending_bytes = exact;
+            continue;
+        }
+
+        let record = pending.pop().expect("a record was just pushed");
+        bodies.extend(render(&pending));
+        pending = vec![record];
+        pending_bytes = single;
+    }
+
+    if !pending.is_empty() {
+        bodies.extend(render(&pending));
+    }
+    bodies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_array;
+
+    #[test]
+    fn test_split_batches() {
+        let records: Vec<LogInfo> = (0..7)
+            .map(|i| LogInfo::new("info", &format!("request {}", i)))
+            .collect();
+
+        let by_count = split_batches(
+            records.clone(),
+            &json_array(),
+            Some([("max_records".to_string(), "3".to_string())].into()),
+        );
+        assert_eq!(by_count.len(), 3);
+        assert!(by_count[2].starts_with(r#"[{"level":"info","message":"request 6"}"#));
+
+        // Each record is 38 bytes, so an envelope holds 2 of them in 88 bytes but not 3
+        let format = json_array().with_option("envelope", "logs");
+        let by_size = split_batches(
+            records.clone(),
+            &format,
+            Some([("max_bytes".to_string(), "100".to_string())].into()),
+        );
+        assert_eq!(by_size.len(), 4);
+        assert!(by_size.iter().all(|body| body.len() <= 100));
+
+        let all: Vec<serde_json::Value> = by_size
+            .iter()
+            .flat_map(|body| {
+                let envelope: serde_json::Value = serde_json::from_str(body).unwrap();
+                envelope["logs"].as_array().unwrap().clone()
+            })
+            .collect();
+        assert_eq!(all.len(), 7);
+        assert_eq!(all[6]["message"], "request 6");
+
+        let oversized = split_batches(
+            records,
+            &format,
+            Some([("max_bytes".to_string(), "10".to_string())].into()),
+        );
+        assert_eq!(oversized.len(), 7);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::utils::host::{hostname, process_name};
+use crate::utils::levels::bunyan_level;
+use crate::utils::time::record_time_or_now;
+use crate::{Format, FormatOptions, LogInfo};
+use chrono::SecondsFormat;
+use serde_json::{json, Map, Value};
+
+/// Renders the log info as a bunyan record, readable by the `bunyan` CLI.
+pub fn bunyan() -> Format {
+    Format::new(|info: LogInfo, opts: FormatOptions| {
+        let opt = |key: &str| opts.as_ref().and_then(|o| o.get(key)).cloned();
+
+        let mut record = Map::new();
+
+        // Meta is flattened into the record, the core fields win on collisions
+        for (key, value) in &info.meta {
+            record.insert(key.clone(), value.clone());
+        }
+        record.remove("timestamp");
+        record.remove("splat");
+
+        let time = record_time_or_now(&info, "timestamp");
+
+        record.insert("v".to_string(), json!(0));
+        record.insert("level".to_string(), json!(bunyan_level(&info.level)));
+        record.insert(
+            "name".to_string(),
+            json!(opt("name").unwrap_or_else(|| process_name().to_string())),
+        );
+        record.insert(
+            "hostname".to_string(),
+            json!(opt("hostname").unwrap_or_else(|| hostname().to_string())),
+        );
+        record.insert("pid".to_string(), json!(std::process::id()));
+        record.insert(
+            "time".to_string(),
+            json!(time.to_rfc3339_opts(SecondsFormat::Millis, true)),
+        );
+        record.insert("msg".to_string(), json!(info.message));
+
+        Some(LogInfo {
+            level: info.level,
+            message: Value::Object(record).to_string(),
+            meta: info.meta,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bunyan_formatter() {
+        let formatter = bunyan()
+            .with_option("name", "checkout")
+            .with_option("hostname", "web-1");
+
+        let info = LogInfo::new("warn", "Slow request")
+            .add_meta("timestamp", "2024-08-27 02:39:15")
+            .add_meta("duration_ms", 1503)
+            .add_meta("level", "ignored");
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        let record: Value = serde_json::from_str(&result.message).unwrap();
+        assert_eq!(
+            record,
+            json!({
+                "v": 0,
+                "level": 40,
+                "name": "checkout",
+                "hostname": "web-1",
+                "pid": std::process::id(),
+                "time": "2024-08-27T02:39:15.000Z",
+                "msg": "Slow request",
+                "duration_ms": 1503,
+            })
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use crate::utils::record::{decode_object, encode_object};
+use crate::{DecodeError, Format, FormatOptions, LogInfo};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::Value;
+
+/// Encodes a log info as a CBOR map with the same shape as `json()`.
+pub fn encode_cbor(info: &LogInfo) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&encode_object(info), &mut bytes)
+        .expect("encoding a JSON value into memory cannot fail");
+    bytes
+}
+
+/// Decodes a CBOR map produced by `encode_cbor` back into a log info.
+pub fn decode_cbor(bytes: &[u8]) -> Result<LogInfo, DecodeError> {
+    let value: Value =
+        ciborium::from_reader(bytes).map_err(|e| DecodeError::Invalid(e.to_string()))?;
+    decode_object(value)
+}
+
+/// Replaces the message with the base64 of the CBOR encoding of the log info,
+/// since `message` can only hold text. Use `encode_cbor` to get the raw bytes.
+pub fn cbor() -> Format {
+    Format::new(|mut info: LogInfo, _opts: FormatOptions| {
+        info.message = STANDARD.encode(encode_cbor(&info));
+        Some(info)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+    use serde_json::json;
+
+    #[test]
+    fn test_cbor_round_trip() {
+        let info = LogInfo::new("warn", "Disk almost full")
+            .add_meta("free_bytes", u64::MAX)
+            .add_meta("ratio", 0.95)
+            .add_meta("mounts", json!({"/": {"ro": false}, "/tmp": null}));
+
+        let bytes = encode_cbor(&info);
+        let decoded = decode_cbor(&bytes).unwrap();
+
+        let expected = json().transform(info.clone(), None).unwrap().message;
+        let actual = json().transform(decoded, None).unwrap().message;
+        assert_eq!(actual, expected);
+
+        let formatted = cbor().transform(info, None).unwrap();
+        assert_eq!(STANDARD.decode(formatted.message).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_cbor_core_key_collisions() {
+        let info = LogInfo::new("info", "hi")
+            .add_meta("level", "meta level")
+            .add_meta("message", "meta message")
+            .add_meta("meta_level", "prefixed")
+            .add_meta("meta_data", 1);
+
+        let decoded = decode_cbor(&encode_cbor(&info)).unwrap();
+        assert_eq!(decoded.level, "info");
+        assert_eq!(decoded.message, "hi");
+        assert_eq!(decoded.meta, info.meta);
+    }
+
+    #[test]
+    fn test_cbor_decode_errors() {
+        assert!(matches!(decode_cbor(&[0xff]), Err(DecodeError::Invalid(_))));
+
+        let mut not_a_record = Vec::new();
+        ciborium::into_writer(&json!([1, 2]), &mut not_a_record).unwrap();
+        assert_eq!(
+            decode_cbor(&not_a_record).unwrap_err(),
+            DecodeError::NotAnObject
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use crate::utils::fields::{lookup_field, meta_root, value_to_text};
+use crate::{Format, FormatOptions, LogInfo};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+const DEFAULT_COLUMNS: &str = "timestamp,level,message";
+
+#[derive(Clone)]
+pub struct Csv {
+    columns: Vec<String>,
+    delimiter: String,
+    overflow: Option<String>,
+}
+
+impl Csv {
+    pub fn new(opts: Option<HashMap<String, String>>) -> Self {
+        let options = opts.unwrap_or_default();
+
+        let columns = options
+            .get("columns")
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_COLUMNS)
+            .split(',')
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect();
+
+        // An empty delimiter would run the cells together
+        let delimiter = options
+            .get("delimiter")
+            .filter(|d| !d.is_empty())
+            .cloned()
+            .unwrap_or_else(|| ",".to_string());
+
+        let overflow = options.get("overflow").filter(|o| !o.is_empty()).cloned();
+
+        Csv {
+            columns,
+            delimiter,
+            overflow,
+        }
+    }
+
+    /// The header line naming every column, in order, including the overflow column.
+    pub fn header(&self) -> String {
+        let mut names: Vec<&str> = self.columns.iter().map(String::as_str).collect();
+        if let Some(overflow) = &self.overflow {
+            names.push(overflow);
+        }
+        self.join(names.into_iter().map(|n| n.to_string()))
+    }
+
+    pub fn row(&self, info: &LogInfo) -> String {
+        let mut cells: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| {
+                lookup_field(info, column)
+                    .map(|v| match v {
+                        Value::Null => String::new(),
+                        other => value_to_text(&other),
+                    })
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        if self.overflow.is_some() {
+            // Everything in meta that no column refers to ends up in the overflow column
+            let listed: Vec<&str> = self.columns.iter().filter_map(|c| meta_root(c)).collect();
+            let rest: Map<String, Value> = info
+                .meta
+                .iter()
+                .filter(|(k, _)| !listed.contains(&k.as_str()))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+
+            cells.push(if rest.is_empty() {
+                String::new()
+            } else {
+                Value::Object(rest).to_string()
+            });
+        }
+
+        self.join(cells.into_iter())
+    }
+
+    fn join(&self, cells: impl Iterator<Item = String>) -> String {
+        cells
+            .map(|cell| escape_cell(&cell, &self.delimiter))
+            .collect::<Vec<_>>()
+            .join(&self.delimiter)
+    }
+}
+
+/// Quotes a cell per RFC 4180 when it contains the delimiter, a quote or a line break.
+fn escape_cell(cell: &str, delimiter: &str) -> String {
+    let needs_quoting = cell.contains(delimiter)
+        || cell.contains('"')
+        || cell.contains('\n')
+        || cell.contains('\r');
+
+    if needs_quoting {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+pub fn csv() -> Format {
+    Format::new(|mut info: LogInfo, opts: FormatOptions| {
+        info.message = Csv::new(opts).row(&info);
+        Some(info)
+    })
+}
+
+/// Returns the header line matching the rows produced by the given `csv()` format.
+pub fn csv_header(format: &Format) -> String {
+    Csv::new(format.options.clone()).header()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_csv_formatter() {
+        let formatter = csv()
+            .with_option("columns", "timestamp, level, message, meta.user_id")
+            .with_option("overflow", "extra");
+
+        let info = LogInfo::new("info", "User said \"hi\", then left")
+            .add_meta("timestamp", "2024-08-27 02:39:15")
+            .add_meta("user_id", 12345)
+            .add_meta("session_id", "abcde12345");
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        assert_eq!(
+            csv_header(&formatter),
+            "timestamp,level,message,meta.user_id,extra"
+        );
+        assert_eq!(
+            result.message,
+            r#"2024-08-27 02:39:15,info,"User said ""hi"", then left",12345,"{""session_id"":""abcde12345""}""#
+        );
+    }
+
+    #[test]
+    fn test_tsv_formatter() {
+        let formatter = csv()
+            .with_option("columns", "level,message,request.path")
+            .with_option("delimiter", "\t");
+
+        let info = LogInfo::new("warn", "multi\nline")
+            .add_meta("request", json!({"path": "/health"}))
+            .add_meta("ignored", true);
+
+        let result = formatter.transform(info, None).unwrap();
+        assert_eq!(result.message, "warn\t\"multi\nline\"\t/health");
+        assert_eq!(csv_header(&formatter), "level\tmessage\trequest.path");
+    }
+
+    #[test]
+    fn test_csv_empty_delimiter() {
+        let formatter = csv()
+            .with_option("columns", "level,message")
+            .with_option("delimiter", "");
+
+        let result = formatter
+            .transform(LogInfo::new("info", "hi"), None)
+            .unwrap();
+        assert_eq!(result.message, "info,hi");
+    }
+}
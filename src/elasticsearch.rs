@@ -0,0 +1,131 @@
+use crate::utils::time::record_time_or_now;
+use crate::{Format, FormatOptions, LogInfo};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+
+/// Expands `strftime` placeholders in the index pattern with the record time,
+/// keeping the pattern as is when it is not a valid format.
+fn index_name(pattern: &str, info: &LogInfo, timestamp_key: &str) -> String {
+    let time = record_time_or_now(info, timestamp_key);
+    let mut name = String::new();
+    match write!(name, "{}", time.format(pattern)) {
+        Ok(()) => name,
+        Err(_) => pattern.to_string(),
+    }
+}
+
+/// Encodes a batch of records as an Elasticsearch `_bulk` NDJSON body: an action
+/// line followed by the document rendered with `document_format`, for each record.
+///
+/// - `index`: target index, with `strftime` placeholders filled from the record
+///   time, e.g. `logs-%Y.%m.%d` (default `logs`).
+/// - `action`: `index` (default) or `create`, which data streams require.
+/// - `id`: set to `hash` to add an `_id` derived from the SHA-256 of the document,
+///   so that retried batches don't index duplicates.
+///
+/// Output that isn't a JSON object, e.g. from `simple()`, is indexed as the `message`
+/// field of a document.
+pub fn elasticsearch_bulk(
+    records: &[LogInfo],
+    document_format: &Format,
+    opts: FormatOptions,
+) -> String {
+    let opt = |key: &str| opts.as_ref().and_then(|o| o.get(key));
+    let index = opt("index").map_or("logs", String::as_str);
+    let action = opt("action").map_or("index", String::as_str);
+    let hash_ids = opt("id").is_some_and(|v| v == "hash");
+    let timestamp_key = opt("timestamp_key").map_or("timestamp", String::as_str);
+
+    let mut body = String::new();
+
+    for record in records {
+        let Some(document) = document_format.transform(record.clone(), None) else {
+            continue;
+        };
+
+        // A document spanning several lines would break the NDJSON framing
+        let document = match serde_json::from_str::<Value>(&document.message) {
+            Ok(value @ Value::Object(_)) if document.message.contains('\n') => value.to_string(),
+            Ok(Value::Object(_)) => document.message,
+            _ => json!({ "message": document.message }).to_string(),
+        };
+
+        let mut metadata = json!({ "_index": index_name(index, record, timestamp_key) });
+        if hash_ids {
+            let digest = Sha256::digest(document.as_bytes());
+            let id: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+            metadata["_id"] = Value::String(id);
+        }
+
+        body.push_str(&json!({ action: metadata }).to_string());
+        body.push('\n');
+        body.push_str(&document);
+        body.push('\n');
+    }
+
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{json, simple};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_elasticsearch_bulk() {
+        let records = vec![
+            LogInfo::new("info", "first").add_meta("timestamp", "2024-08-27T23:59:59Z"),
+            LogInfo::new("error", "second").add_meta("timestamp", "2024-08-28T00:00:01Z"),
+        ];
+
+        let opts = HashMap::from([
+            ("index".to_string(), "logs-%Y.%m.%d".to_string()),
+            ("id".to_string(), "hash".to_string()),
+        ]);
+        let body = elasticsearch_bulk(&records, &json(), Some(opts.clone()));
+        println!("{}", body);
+
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(body.ends_with('\n'));
+
+        let action: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(action["index"]["_index"], "logs-2024.08.27");
+        assert_eq!(action["index"]["_id"].as_str().unwrap().len(), 64);
+        assert_eq!(
+            lines[1],
+            r#"{"level":"info","message":"first","timestamp":"2024-08-27T23:59:59Z"}"#
+        );
+
+        let action: Value = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(action["index"]["_index"], "logs-2024.08.28");
+
+        // the same records always get the same ids
+        assert_eq!(elasticsearch_bulk(&records, &json(), Some(opts)), body);
+    }
+
+    #[test]
+    fn test_elasticsearch_bulk_multiline_documents() {
+        let records = vec![LogInfo::new("info", "hi")];
+        let opts = HashMap::from([("action".to_string(), "create".to_string())]);
+
+        let body = elasticsearch_bulk(&records, &json().with_option("space", "2"), Some(opts));
+        assert_eq!(
+            body,
+            "{\"create\":{\"_index\":\"logs\"}}\n{\"level\":\"info\",\"message\":\"hi\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_elasticsearch_bulk_text_documents() {
+        let records = vec![LogInfo::new("info", "line1\nline2")];
+
+        let body = elasticsearch_bulk(&records, &simple(), None);
+        assert_eq!(
+            body,
+            "{\"index\":{\"_index\":\"logs\"}}\n{\"message\":\"info: line1\\nline2\"}\n"
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use crate::utils::time::record_time_or_now;
+use crate::{Format, FormatOptions, LogInfo};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// CloudWatch rejects documents declaring more metrics than this.
+const MAX_METRICS: usize = 100;
+/// CloudWatch rejects dimension sets with more keys than this.
+const MAX_DIMENSIONS: usize = 30;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmfError {
+    MissingNamespace,
+    InvalidOption(String),
+    TooManyMetrics(usize),
+    TooManyDimensions(usize),
+    MissingMetric(String),
+    NonNumericMetric(String),
+    MissingDimension(String),
+}
+
+impl fmt::Display for EmfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EmfError::MissingNamespace => write!(f, "no `namespace` option given"),
+            EmfError::InvalidOption(key) => write!(f, "option `{}` is not valid JSON", key),
+            EmfError::TooManyMetrics(n) => {
+                write!(f, "{} metrics declared, at most {} allowed", n, MAX_METRICS)
+            }
+            EmfError::TooManyDimensions(n) => write!(
+                f,
+                "dimension set has {} keys, at most {} allowed",
+                n, MAX_DIMENSIONS
+            ),
+            EmfError::MissingMetric(name) => write!(f, "metric `{}` is missing", name),
+            EmfError::NonNumericMetric(name) => write!(f, "metric `{}` is not numeric", name),
+            EmfError::MissingDimension(name) => {
+                write!(f, "dimension `{}` is missing or not a string", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EmfError {}
+
+fn is_numeric(value: &Value) -> bool {
+    match value {
+        Value::Number(_) => true,
+        // EMF allows up to 100 values per metric in an array
+        Value::Array(values) => {
+            !values.is_empty() && values.len() <= MAX_METRICS && values.iter().all(Value::is_number)
+        }
+        _ => false,
+    }
+}
+
+/// The record as EMF lays it out, without the `_aws` metadata: meta at the top level
+/// along with the level and message.
+fn plain_document(info: &LogInfo) -> Map<String, Value> {
+    let mut document = Map::new();
+    for (key, value) in &info.meta {
+        document.insert(key.clone(), value.clone());
+    }
+    document.remove("timestamp");
+    document.insert("level".to_string(), Value::String(info.level.clone()));
+    document.insert("message".to_string(), Value::String(info.message.clone()));
+    document
+}
+
+/// Builds the EMF document for a log info, validating it against the declared
+/// metrics and dimensions.
+pub fn emf_document(info: &LogInfo, opts: FormatOptions) -> Result<String, EmfError> {
+    let opts = opts.unwrap_or_default();
+
+    let namespace = opts.get("namespace").ok_or(EmfError::MissingNamespace)?;
+    let dimensions: Vec<Vec<String>> = match opts.get("dimensions") {
+        Some(raw) => serde_json::from_str(raw)
+            .map_err(|_| EmfError::InvalidOption("dimensions".to_string()))?,
+        None => Vec::new(),
+    };
+    let metrics: HashMap<String, String> = match opts.get("metrics") {
+        Some(raw) => {
+            serde_json::from_str(raw).map_err(|_| EmfError::InvalidOption("metrics".to_string()))?
+        }
+        None => HashMap::new(),
+    };
+
+    if metrics.len() > MAX_METRICS {
+        return Err(EmfError::TooManyMetrics(metrics.len()));
+    }
+
+    let mut metric_names: Vec<&String> = metrics.keys().collect();
+    metric_names.sort();
+
+    for name in &metric_names {
+        match info.meta.get(name.as_str()) {
+            None => return Err(EmfError::MissingMetric(name.to_string())),
+            Some(value) if !is_numeric(value) => {
+                return Err(EmfError::NonNumericMetric(name.to_string()))
+            }
+            Some(_) => {}
+        }
+    }
+
+    for set in &dimensions {
+        if set.len() > MAX_DIMENSIONS {
+            return Err(EmfError::TooManyDimensions(set.len()));
+        }
+        for key in set {
+            if !info.meta.get(key).is_some_and(Value::is_string) {
+                return Err(EmfError::MissingDimension(key.clone()));
+            }
+        }
+    }
+
+    let metric_definitions: Vec<Value> = metric_names
+        .iter()
+        .map(|name| json!({ "Name": name, "Unit": metrics[name.as_str()] }))
+        .collect();
+
+    let timestamp = record_time_or_now(info, "timestamp").timestamp_millis();
+
+    let mut document = plain_document(info);
+    document.insert(
+        "_aws".to_string(),
+        json!({
+            "Timestamp": timestamp,
+            "CloudWatchMetrics": [{
+                "Namespace": namespace,
+                "Dimensions": dimensions,
+                "Metrics": metric_definitions,
+            }],
+        }),
+    );
+
+    Ok(Value::Object(document).to_string())
+}
+
+/// Renders the log info as a CloudWatch Embedded Metric Format document.
+///
+/// Records that would not make a valid document are still logged, as the same JSON
+/// without the `_aws` metadata so no metrics are extracted from them, with the reason
+/// under `emf_error` in both the document and the meta. With `on_invalid=drop` they
+/// are filtered out instead.
+pub fn emf() -> Format {
+    Format::new(|mut info: LogInfo, opts: FormatOptions| {
+        let drop_invalid = opts
+            .as_ref()
+            .and_then(|o| o.get("on_invalid"))
+            .is_some_and(|v| v == "drop");
+
+        info.message = match emf_document(&info, opts) {
+            Ok(document) => document,
+            Err(_) if drop_invalid => return None,
+            Err(e) => {
+                info.meta
+                    .insert("emf_error".to_string(), Value::String(e.to_string()));
+                Value::Object(plain_document(&info)).to_string()
+            }
+        };
+        Some(info)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formatter() -> Format {
+        emf()
+            .with_option("namespace", "checkout")
+            .with_option("dimensions", r#"[["service"], ["service", "region"]]"#)
+            .with_option(
+                "metrics",
+                r#"{"latency": "Milliseconds", "items": "Count"}"#,
+            )
+    }
+
+    #[test]
+    fn test_emf_formatter() {
+        let info = LogInfo::new("info", "Order placed")
+            .add_meta("timestamp", "2024-08-27T02:39:15Z")
+            .add_meta("service", "api")
+            .add_meta("region", "eu-west-1")
+            .add_meta("latency", 12.5)
+            .add_meta("items", json!([1, 3]))
+            .add_meta("order_id", "A-1");
+
+        let result = formatter().transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        let document: Value = serde_json::from_str(&result.message).unwrap();
+        assert_eq!(
+            document,
+            json!({
+                "_aws": {
+                    "Timestamp": 1724726355000i64,
+                    "CloudWatchMetrics": [{
+                        "Namespace": "checkout",
+                        "Dimensions": [["service"], ["service", "region"]],
+                        "Metrics": [
+                            {"Name": "items", "Unit": "Count"},
+                            {"Name": "latency", "Unit": "Milliseconds"},
+                        ],
+                    }],
+                },
+                "level": "info",
+                "message": "Order placed",
+                "service": "api",
+                "region": "eu-west-1",
+                "latency": 12.5,
+                "items": [1, 3],
+                "order_id": "A-1",
+            })
+        );
+    }
+
+    #[test]
+    fn test_emf_rejects_invalid_records() {
+        let info = LogInfo::new("info", "Order placed")
+            .add_meta("service", "api")
+            .add_meta("region", "eu-west-1")
+            .add_meta("latency", "fast")
+            .add_meta("items", 2);
+
+        let format = formatter();
+        let result = format.transform(info.clone(), None).unwrap();
+        assert_eq!(
+            result.message,
+            r#"{"emf_error":"metric `latency` is not numeric","items":2,"latency":"fast","level":"info","message":"Order placed","region":"eu-west-1","service":"api"}"#
+        );
+        assert_eq!(result.meta["emf_error"], "metric `latency` is not numeric");
+        assert!(format
+            .clone()
+            .with_option("on_invalid", "drop")
+            .transform(info.clone(), None)
+            .is_none());
+        assert_eq!(
+            emf_document(&info, format.options.clone()),
+            Err(EmfError::NonNumericMetric("latency".to_string()))
+        );
+
+        let too_many: HashMap<String, String> = (0..=MAX_METRICS)
+            .map(|i| (format!("m{}", i), "Count".to_string()))
+            .collect();
+        let opts = HashMap::from([
+            ("namespace".to_string(), "checkout".to_string()),
+            (
+                "metrics".to_string(),
+                serde_json::to_string(&too_many).unwrap(),
+            ),
+        ]);
+        assert_eq!(
+            emf_document(&info, Some(opts)),
+            Err(EmfError::TooManyMetrics(101))
+        );
+
+        assert_eq!(emf_document(&info, None), Err(EmfError::MissingNamespace));
+    }
+}
@@ -0,0 +1,52 @@
+use std::fmt;
+
+/// Returned when bytes produced by one of the binary encoders can't be turned back
+/// into a `LogInfo`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    /// The bytes are not valid for the encoding.
+    Invalid(String),
+    /// The decoded value is not a map/object.
+    NotAnObject,
+    /// A core field is missing or is not a string.
+    MissingField(&'static str),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Invalid(reason) => write!(f, "invalid encoding: {}", reason),
+            DecodeError::NotAnObject => write!(f, "the decoded record is not a map"),
+            DecodeError::MissingField(field) => {
+                write!(f, "the decoded record has no string `{}` field", field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Returned when a line can't be parsed back into a `LogInfo`, with the byte offset
+/// in the line where parsing failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub offset: usize,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(offset: usize, reason: impl Into<String>) -> Self {
+        ParseError {
+            offset,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at byte {}", self.reason, self.offset)
+    }
+}
+
+impl std::error::Error for ParseError {}
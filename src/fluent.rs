@@ -0,0 +1,239 @@
+use crate::utils::record::encode_object;
+use crate::utils::time::{record_time_or_now, unix_nanos};
+use crate::{DecodeError, FormatOptions, LogInfo};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Timelike, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// MessagePack ext type of the Forward protocol's `EventTime`.
+const EVENT_TIME_EXT: i8 = 0;
+
+const WRITE: &str = "writing to a Vec cannot fail";
+
+static CHUNK_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// A Forward protocol message ready to be written to a fluentd/fluent-bit socket.
+#[derive(Debug, Clone)]
+pub struct ForwardMessage {
+    pub bytes: Vec<u8>,
+    /// The chunk id the server must acknowledge, when an ack was requested.
+    pub chunk: Option<String>,
+}
+
+/// Encodes a batch of records as a Forward mode message:
+/// `[tag, [[time, record], ...], option]`.
+///
+/// Times are `EventTime` ext values (nanosecond precision) read from the
+/// `timestamp` meta, or the current time. With `require_ack=true` a unique
+/// `chunk` id is added to the options, which the server echoes back in its ack
+/// (see `fluent_ack`).
+pub fn fluent_forward(tag: &str, records: &[LogInfo], opts: FormatOptions) -> ForwardMessage {
+    let opt = |key: &str| opts.as_ref().and_then(|o| o.get(key));
+    let timestamp_key = opt("timestamp_key").map_or("timestamp", String::as_str);
+    let require_ack = opt("require_ack").is_some_and(|v| v == "true");
+
+    let mut bytes = Vec::new();
+    let buf = &mut bytes;
+
+    rmp::encode::write_array_len(buf, 3).expect(WRITE);
+    rmp::encode::write_str(buf, tag).expect(WRITE);
+
+    let records = &records[..clamped_len(records.len()) as usize];
+    rmp::encode::write_array_len(buf, records.len() as u32).expect(WRITE);
+    for record in records {
+        let time = record_time_or_now(record, timestamp_key);
+        let mut object = encode_object(record);
+        if let Value::Object(map) = &mut object {
+            map.remove(timestamp_key);
+        }
+
+        rmp::encode::write_array_len(buf, 2).expect(WRITE);
+        write_event_time(buf, &time);
+        write_value(buf, &object);
+    }
+
+    let chunk = require_ack.then(new_chunk_id);
+
+    let mut option = serde_json::Map::new();
+    option.insert("size".to_string(), Value::from(records.len()));
+    if let Some(chunk) = &chunk {
+        option.insert("chunk".to_string(), Value::String(chunk.clone()));
+    }
+    write_value(buf, &Value::Object(option));
+
+    ForwardMessage { bytes, chunk }
+}
+
+/// Reads the chunk id from a server's `{"ack": chunk}` response.
+pub fn fluent_ack(response: &[u8]) -> Result<String, DecodeError> {
+    let mut response: HashMap<String, Value> =
+        rmp_serde::from_slice(response).map_err(|e| DecodeError::Invalid(e.to_string()))?;
+    match response.remove("ack") {
+        Some(Value::String(chunk)) => Ok(chunk),
+        _ => Err(DecodeError::MissingField("ack")),
+    }
+}
+
+fn new_chunk_id() -> String {
+    let mut id = Vec::with_capacity(16);
+    id.extend_from_slice(&unix_nanos(&Utc::now()).to_be_bytes());
+    id.extend_from_slice(&std::process::id().to_be_bytes());
+    id.extend_from_slice(&CHUNK_COUNTER.fetch_add(1, Ordering::Relaxed).to_be_bytes());
+    STANDARD.encode(id)
+}
+
+/// MessagePack arrays and maps hold at most `u32::MAX` items; longer ones are cut
+/// to that length.
+fn clamped_len(len: usize) -> u32 {
+    u32::try_from(len).unwrap_or(u32::MAX)
+}
+
+/// `EventTime` seconds are an unsigned 32-bit epoch, so times before 1970 are
+/// written as the epoch and times after 2106 as the last representable second.
+fn write_event_time(buf: &mut Vec<u8>, time: &DateTime<Utc>) {
+    let (seconds, nanos) = match u32::try_from(time.timestamp()) {
+        Ok(seconds) => (seconds, time.nanosecond() % 1_000_000_000),
+        Err(_) if time.timestamp() < 0 => (0, 0),
+        Err(_) => (u32::MAX, 999_999_999),
+    };
+    rmp::encode::write_ext_meta(buf, 8, EVENT_TIME_EXT).expect(WRITE);
+    buf.extend_from_slice(&seconds.to_be_bytes());
+    buf.extend_from_slice(&nanos.to_be_bytes());
+}
+
+fn write_value(buf: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => rmp::encode::write_nil(buf).expect(WRITE),
+        Value::Bool(b) => rmp::encode::write_bool(buf, *b).expect(WRITE),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                rmp::encode::write_sint(buf, i).expect(WRITE);
+            } else if let Some(u) = n.as_u64() {
+                rmp::encode::write_uint(buf, u).expect(WRITE);
+            } else {
+                rmp::encode::write_f64(buf, n.as_f64().unwrap_or_default()).expect(WRITE);
+            }
+        }
+        Value::String(s) => rmp::encode::write_str(buf, s).expect(WRITE),
+        Value::Array(arr) => {
+            let len = clamped_len(arr.len());
+            rmp::encode::write_array_len(buf, len).expect(WRITE);
+            for item in arr.iter().take(len as usize) {
+                write_value(buf, item);
+            }
+        }
+        Value::Object(map) => {
+            let len = clamped_len(map.len());
+            rmp::encode::write_map_len(buf, len).expect(WRITE);
+            for (key, item) in map.iter().take(len as usize) {
+                rmp::encode::write_str(buf, key).expect(WRITE);
+                write_value(buf, item);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmpv::Value as MsgValue;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    #[test]
+    fn test_fluent_forward_over_tcp() {
+        // Stands in for fluent-bit's forward input: reads one message and acks its chunk
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let message = rmpv::decode::read_value(&mut stream).unwrap();
+
+            let chunk = message[2]["chunk"].as_str().unwrap().to_string();
+            let ack = MsgValue::Map(vec![("ack".into(), chunk.into())]);
+            rmpv::encode::write_value(&mut stream, &ack).unwrap();
+            message
+        });
+
+        let records = vec![
+            LogInfo::new("info", "first")
+                .add_meta("timestamp", "2024-08-27T02:39:15.123456789Z")
+                .add_meta("user_id", 12345),
+            LogInfo::new("error", "second").add_meta("tags", serde_json::json!(["a", 1.5])),
+        ];
+        let opts = HashMap::from([("require_ack".to_string(), "true".to_string())]);
+        let message = fluent_forward("app.logs", &records, Some(opts));
+        let chunk = message.chunk.clone().unwrap();
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(&message.bytes).unwrap();
+
+        let mut response = [0u8; 64];
+        let read = stream.read(&mut response).unwrap();
+        assert_eq!(fluent_ack(&response[..read]).unwrap(), chunk);
+
+        let received = server.join().unwrap();
+        assert_eq!(received[0].as_str(), Some("app.logs"));
+
+        let entries = received[1].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let MsgValue::Ext(EVENT_TIME_EXT, time) = &entries[0][0] else {
+            panic!("expected an EventTime, got {:?}", entries[0][0]);
+        };
+        assert_eq!(
+            u32::from_be_bytes(time[..4].try_into().unwrap()),
+            1724726355
+        );
+        assert_eq!(u32::from_be_bytes(time[4..].try_into().unwrap()), 123456789);
+
+        let first = entries[0][1].as_map().unwrap();
+        let field = |name: &str| {
+            first
+                .iter()
+                .find(|(k, _)| k.as_str() == Some(name))
+                .map(|(_, v)| v.clone())
+        };
+        assert_eq!(field("message"), Some("first".into()));
+        assert_eq!(field("user_id"), Some(12345.into()));
+        assert_eq!(field("timestamp"), None);
+
+        assert_eq!(received[2]["size"].as_u64(), Some(2));
+    }
+
+    #[test]
+    fn test_fluent_forward_without_ack() {
+        let message = fluent_forward("app", &[LogInfo::new("info", "hi")], None);
+        assert!(message.chunk.is_none());
+
+        let decoded = rmpv::decode::read_value(&mut message.bytes.as_slice()).unwrap();
+        assert!(decoded[2]["chunk"].is_nil());
+        assert!(fluent_ack(b"\x80").is_err());
+    }
+
+    #[test]
+    fn test_fluent_event_time_out_of_range() {
+        let records = [
+            LogInfo::new("info", "old").add_meta("timestamp", "1969-07-20T20:17:40Z"),
+            LogInfo::new("info", "far").add_meta("timestamp", "2200-01-01T00:00:00Z"),
+        ];
+        let message = fluent_forward("app", &records, None);
+        let decoded = rmpv::decode::read_value(&mut message.bytes.as_slice()).unwrap();
+
+        let times: Vec<Vec<u8>> = decoded[1]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| match &entry[0] {
+                MsgValue::Ext(EVENT_TIME_EXT, time) => time.clone(),
+                other => panic!("expected an EventTime, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(times[0], [0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(times[1][..4], u32::MAX.to_be_bytes());
+    }
+}
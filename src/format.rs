@@ -76,9 +76,9 @@ mod tests {
     fn test_custom_format() {
         let volume = Format::new(|mut info: LogInfo, opts: FormatOptions| {
             if let Some(opts) = opts {
-                if opts.get("yell").is_some() {
+                if opts.contains_key("yell") {
                     info.message = info.message.to_uppercase();
-                } else if opts.get("whisper").is_some() {
+                } else if opts.contains_key("whisper") {
                     info.message = info.message.to_lowercase();
                 }
             }
@@ -0,0 +1,618 @@
+use std::fmt;
+use std::io::{self, Read};
+
+/// The longest length accepted in an RFC 6587 octet count, 999,999,999 bytes.
+const MAX_OCTET_COUNT_DIGITS: usize = 9;
+/// The largest record a decoder accepts unless told otherwise, 16 MiB.
+const DEFAULT_MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// How records are delimited when written one after another to a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// One record per line. Pretty-printed JSON is written compactly by removing the
+    /// whitespace between tokens; in other records backslashes and line breaks are
+    /// escaped as `\\`, `\n` and `\r`, and the decoder undoes the escapes. An empty
+    /// record is written as a lone `\`, since blank lines are skipped when reading.
+    Ndjson,
+    /// Each record is preceded by its length in bytes, as a 4-byte big-endian integer.
+    LengthPrefixed,
+    /// RFC 6587 octet counting: the length in bytes as decimal digits, a space, then the record.
+    OctetCounting,
+    /// Each record is followed by a NUL byte, as used by GELF over TCP. NUL bytes inside
+    /// a record are removed.
+    NullTerminated,
+}
+
+#[derive(Debug)]
+pub enum FrameError {
+    Io(io::Error),
+    /// An octet count that is not a number or is too long.
+    InvalidLength(String),
+    /// The stream ended in the middle of a frame.
+    Truncated {
+        expected: usize,
+        available: usize,
+    },
+    /// A record longer than a 4-byte length prefix can describe.
+    TooLong(usize),
+    /// A frame longer than the decoder's `max_frame_len`.
+    FrameTooLarge {
+        len: usize,
+        max: usize,
+    },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FrameError::Io(e) => write!(f, "failed to read frames: {}", e),
+            FrameError::InvalidLength(raw) => write!(f, "invalid frame length `{}`", raw),
+            FrameError::Truncated {
+                expected,
+                available,
+            } => write!(
+                f,
+                "stream ended inside a frame: expected {} bytes, got {}",
+                expected, available
+            ),
+            FrameError::TooLong(len) => {
+                write!(f, "record of {} bytes is too long for a length prefix", len)
+            }
+            FrameError::FrameTooLarge { len, max } => {
+                write!(f, "frame of {} bytes exceeds the maximum of {}", len, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FrameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FrameError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FrameError {
+    fn from(e: io::Error) -> Self {
+        FrameError::Io(e)
+    }
+}
+
+fn is_json_container(line: &[u8]) -> bool {
+    serde_json::from_slice::<serde_json::Value>(line)
+        .is_ok_and(|value| value.is_object() || value.is_array())
+}
+
+/// Removes the whitespace between the tokens of a JSON document, leaving the keys,
+/// their order and the numbers exactly as written.
+fn compact_json(json: &[u8]) -> Vec<u8> {
+    let mut compact = Vec::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for &b in json {
+        if in_string {
+            compact.push(b);
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+        } else if !matches!(b, b' ' | b'\t' | b'\n' | b'\r') {
+            in_string = b == b'"';
+            compact.push(b);
+        }
+    }
+    compact
+}
+
+/// Puts a record on a single line, see `Framing::Ndjson`.
+fn single_line(record: &[u8]) -> Vec<u8> {
+    if record.is_empty() {
+        return b"\\".to_vec();
+    }
+
+    // Line breaks in JSON are only ever whitespace, so it can be written compactly
+    if is_json_container(record) {
+        if !record.contains(&b'\n') && !record.contains(&b'\r') {
+            return record.to_vec();
+        }
+        return compact_json(record);
+    }
+
+    let mut line = Vec::with_capacity(record.len() + 8);
+    for &b in record {
+        match b {
+            b'\\' => line.extend_from_slice(b"\\\\"),
+            b'\n' => line.extend_from_slice(b"\\n"),
+            b'\r' => line.extend_from_slice(b"\\r"),
+            other => line.push(other),
+        }
+    }
+    // Escaping can turn text into JSON, e.g. `["a\"]`; a leading backslash, which
+    // escaping never produces, keeps the decoder from taking it as a JSON record
+    if is_json_container(&line) {
+        line.insert(0, b'\\');
+    }
+    line
+}
+
+/// Undoes `single_line` for a line that isn't a JSON object or array.
+fn unescape_line(line: Vec<u8>) -> Vec<u8> {
+    if line == b"\\" {
+        return Vec::new();
+    }
+    if !line.contains(&b'\\') || is_json_container(&line) {
+        return line;
+    }
+
+    let mut record = Vec::with_capacity(line.len());
+    let mut bytes = line.into_iter();
+    while let Some(b) = bytes.next() {
+        if b != b'\\' {
+            record.push(b);
+            continue;
+        }
+        match bytes.next() {
+            Some(b'n') => record.push(b'\n'),
+            Some(b'r') => record.push(b'\r'),
+            Some(other) => record.push(other),
+            None => record.push(b'\\'),
+        }
+    }
+    record
+}
+
+impl Framing {
+    /// Frames a single record, usually the `message` of a formatted log info.
+    pub fn encode(&self, record: impl AsRef<[u8]>) -> Result<Vec<u8>, FrameError> {
+        let record = record.as_ref();
+        let framed = match self {
+            Framing::Ndjson => {
+                let mut line = single_line(record);
+                line.push(b'\n');
+                line
+            }
+            Framing::LengthPrefixed => {
+                let len =
+                    u32::try_from(record.len()).map_err(|_| FrameError::TooLong(record.len()))?;
+                let mut frame = Vec::with_capacity(record.len() + 4);
+                frame.extend_from_slice(&len.to_be_bytes());
+                frame.extend_from_slice(record);
+                frame
+            }
+            Framing::OctetCounting => {
+                let mut frame = format!("{} ", record.len()).into_bytes();
+                frame.extend_from_slice(record);
+                frame
+            }
+            Framing::NullTerminated => {
+                let mut frame: Vec<u8> = record.iter().copied().filter(|&b| b != 0).collect();
+                frame.push(0);
+                frame
+            }
+        };
+        Ok(framed)
+    }
+
+    /// Frames every record and concatenates them into one buffer.
+    pub fn encode_all<I>(&self, records: I) -> Result<Vec<u8>, FrameError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut stream = Vec::new();
+        for record in records {
+            stream.extend(self.encode(record)?);
+        }
+        Ok(stream)
+    }
+}
+
+/// Splits bytes arriving in arbitrary chunks back into records.
+#[derive(Debug, Clone)]
+pub struct FrameDecoder {
+    framing: Framing,
+    buffer: Vec<u8>,
+    max_frame_len: usize,
+}
+
+impl FrameDecoder {
+    pub fn new(framing: Framing) -> Self {
+        FrameDecoder {
+            framing,
+            buffer: Vec::new(),
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    /// Sets the largest record accepted, 16 MiB by default. A longer frame, or a
+    /// length prefix announcing one, is a `FrameError::FrameTooLarge`, so a hostile
+    /// stream can't make the decoder buffer without bound.
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+
+    /// Adds bytes read from the stream.
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the next complete record, or `None` until more bytes arrive.
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>, FrameError> {
+        loop {
+            let Some((start, end)) = self.next_bounds()? else {
+                return Ok(None);
+            };
+            let record = self.buffer[start..end].to_vec();
+            let consumed = match self.framing {
+                Framing::Ndjson | Framing::NullTerminated => end + 1,
+                _ => end,
+            };
+            self.buffer.drain(..consumed);
+
+            if self.framing == Framing::Ndjson {
+                // Blank lines between records are not records
+                if record.is_empty() {
+                    continue;
+                }
+                return Ok(Some(unescape_line(record)));
+            }
+            return Ok(Some(record));
+        }
+    }
+
+    /// Called at the end of the stream. A last line without a newline is returned as a
+    /// record; any other leftover bytes mean the stream was cut off.
+    pub fn finish(&mut self) -> Result<Option<Vec<u8>>, FrameError> {
+        if let Some(record) = self.next_frame()? {
+            return Ok(Some(record));
+        }
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+
+        let rest = std::mem::take(&mut self.buffer);
+        match self.framing {
+            Framing::Ndjson => Ok(Some(unescape_line(strip_cr(rest)))),
+            Framing::NullTerminated => Ok(Some(rest)),
+            Framing::LengthPrefixed | Framing::OctetCounting => {
+                let expected = match self.framing {
+                    Framing::LengthPrefixed if rest.len() >= 4 => {
+                        u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize + 4
+                    }
+                    Framing::OctetCounting => {
+                        octet_count(&rest)?.map_or(rest.len() + 1, |(len, header)| len + header)
+                    }
+                    _ => 4,
+                };
+                Err(FrameError::Truncated {
+                    expected,
+                    available: rest.len(),
+                })
+            }
+        }
+    }
+
+    /// The range of the next complete record in the buffer.
+    fn next_bounds(&self) -> Result<Option<(usize, usize)>, FrameError> {
+        let bounds = self.frame_bounds()?;
+        let len = match (bounds, self.framing) {
+            (Some((start, end)), _) => end - start,
+            // The length of an incomplete frame is known from its prefix
+            (None, Framing::LengthPrefixed) if self.buffer.len() >= 4 => {
+                let b = &self.buffer;
+                u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize
+            }
+            (None, Framing::OctetCounting) => octet_count(&self.buffer)?.map_or(0, |(len, _)| len),
+            // Without a delimiter yet, the whole buffer belongs to the frame
+            (None, Framing::Ndjson | Framing::NullTerminated) => self.buffer.len(),
+            (None, _) => 0,
+        };
+        if len > self.max_frame_len {
+            return Err(FrameError::FrameTooLarge {
+                len,
+                max: self.max_frame_len,
+            });
+        }
+        Ok(bounds)
+    }
+
+    fn frame_bounds(&self) -> Result<Option<(usize, usize)>, FrameError> {
+        let buffer = &self.buffer;
+        let bounds = match self.framing {
+            Framing::Ndjson => buffer.iter().position(|&b| b == b'\n').map(|end| {
+                // Tolerate CRLF line endings
+                let content_end = if end > 0 && buffer[end - 1] == b'\r' {
+                    end - 1
+                } else {
+                    end
+                };
+                (0, content_end)
+            }),
+            Framing::NullTerminated => buffer.iter().position(|&b| b == 0).map(|end| (0, end)),
+            Framing::LengthPrefixed => {
+                if buffer.len() < 4 {
+                    None
+                } else {
+                    let len =
+                        u32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as usize;
+                    (buffer.len() >= 4 + len).then_some((4, 4 + len))
+                }
+            }
+            Framing::OctetCounting => octet_count(buffer)?
+                .filter(|(len, header)| buffer.len() >= header + len)
+                .map(|(len, header)| (header, header + len)),
+        };
+        Ok(bounds)
+    }
+}
+
+/// Parses the `MSG-LEN SP` header of an octet-counted frame, returning the length and
+/// the size of the header, or `None` when the header isn't complete yet.
+fn octet_count(buffer: &[u8]) -> Result<Option<(usize, usize)>, FrameError> {
+    let digits = buffer.iter().take_while(|b| b.is_ascii_digit()).count();
+    let invalid = || {
+        let shown = &buffer[..buffer.len().min(MAX_OCTET_COUNT_DIGITS + 1)];
+        FrameError::InvalidLength(String::from_utf8_lossy(shown).into_owned())
+    };
+
+    if digits > MAX_OCTET_COUNT_DIGITS || (digits == 0 && !buffer.is_empty()) {
+        return Err(invalid());
+    }
+    match buffer.get(digits) {
+        None => Ok(None),
+        Some(b' ') => {
+            let len = std::str::from_utf8(&buffer[..digits])
+                .ok()
+                .and_then(|d| d.parse().ok())
+                .ok_or_else(invalid)?;
+            Ok(Some((len, digits + 1)))
+        }
+        Some(_) => Err(invalid()),
+    }
+}
+
+fn strip_cr(mut line: Vec<u8>) -> Vec<u8> {
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    line
+}
+
+/// Reads records from a stream, e.g. a socket or a file, splitting it with a `FrameDecoder`.
+pub struct FrameReader<R> {
+    reader: R,
+    decoder: FrameDecoder,
+    done: bool,
+}
+
+impl<R: Read> FrameReader<R> {
+    pub fn new(reader: R, framing: Framing) -> Self {
+        FrameReader {
+            reader,
+            decoder: FrameDecoder::new(framing),
+            done: false,
+        }
+    }
+
+    /// Sets the largest record accepted, see `FrameDecoder::with_max_frame_len`.
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.decoder = self.decoder.with_max_frame_len(max_frame_len);
+        self
+    }
+}
+
+impl<R: Read> Iterator for FrameReader<R> {
+    type Item = Result<Vec<u8>, FrameError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = [0u8; 8192];
+        while !self.done {
+            match self.decoder.next_frame() {
+                Ok(Some(record)) => return Some(Ok(record)),
+                Ok(None) => {}
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+
+            match self.reader.read(&mut chunk) {
+                Ok(0) => {
+                    self.done = true;
+                    return self.decoder.finish().transpose();
+                }
+                Ok(n) => self.decoder.extend(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e.into()));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{json, pretty_print, LogInfo};
+
+    /// Reads the stream one byte at a time to exercise partial frames.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((b, rest)) if !buf.is_empty() => {
+                    buf[0] = *b;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    fn read_all(stream: &[u8], framing: Framing) -> Vec<Vec<u8>> {
+        FrameReader::new(Trickle(stream), framing)
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_framing_round_trip() {
+        let records = ["plain", "", "two\nlines", "tab\tand \u{e9}", "1234 5"];
+
+        for framing in [
+            Framing::LengthPrefixed,
+            Framing::OctetCounting,
+            Framing::NullTerminated,
+        ] {
+            let stream = framing.encode_all(records).unwrap();
+            let decoded = read_all(&stream, framing);
+            let expected: Vec<Vec<u8>> = records.iter().map(|r| r.as_bytes().to_vec()).collect();
+            assert_eq!(decoded, expected, "{:?}", framing);
+        }
+
+        assert_eq!(
+            Framing::OctetCounting.encode("caf\u{e9}").unwrap(),
+            b"5 caf\xc3\xa9".to_vec()
+        );
+        assert_eq!(
+            Framing::LengthPrefixed.encode("hi").unwrap(),
+            vec![0, 0, 0, 2, b'h', b'i']
+        );
+    }
+
+    #[test]
+    fn test_ndjson_framing() {
+        let info = LogInfo::new("info", "multi\nline").add_meta("user", "bob");
+        let indented = json()
+            .with_option("space", "2")
+            .transform(info.clone(), None)
+            .unwrap()
+            .message;
+        let compact = json().transform(info.clone(), None).unwrap().message;
+        let pretty = pretty_print().transform(info, None).unwrap().message;
+        assert!(indented.contains('\n') && pretty.contains('\n'));
+
+        let stream = Framing::Ndjson
+            .encode_all([indented.as_str(), pretty.as_str(), "stack\r\n  at main"])
+            .unwrap();
+        assert_eq!(stream.iter().filter(|&&b| b == b'\n').count(), 3);
+
+        let records = read_all(&stream, Framing::Ndjson);
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0], compact.as_bytes());
+        assert_eq!(records[1], pretty.as_bytes());
+        assert_eq!(records[2], b"stack\r\n  at main");
+
+        // A last line without a newline and CRLF endings are both accepted
+        assert_eq!(
+            read_all(b"a\r\n\nb", Framing::Ndjson),
+            vec![b"a".to_vec(), b"b".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_ndjson_backslashes() {
+        let records = [
+            "a\nb",
+            "a\\nb",
+            "C:\\tmp\\",
+            "[\"a\\\"]",
+            "{\"k\":\n\"v\"}x",
+        ];
+        let stream = Framing::Ndjson.encode_all(records).unwrap();
+        assert_eq!(
+            stream,
+            b"a\\nb\na\\\\nb\nC:\\\\tmp\\\\\n\\[\"a\\\\\"]\n{\"k\":\\n\"v\"}x\n".to_vec()
+        );
+
+        let decoded = read_all(&stream, Framing::Ndjson);
+        let expected: Vec<Vec<u8>> = records.iter().map(|r| r.as_bytes().to_vec()).collect();
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn test_ndjson_keeps_json_and_empty_records() {
+        // Compacting keeps the key order, duplicate keys, numbers and string contents
+        let records = [
+            "a",
+            "",
+            "{\"b\": 1.0,\n \"a\": [2, 1e3],\n \"b\": \" x\\\" \"}",
+            "",
+        ];
+        let stream = Framing::Ndjson.encode_all(records).unwrap();
+        assert_eq!(
+            stream,
+            b"a\n\\\n{\"b\":1.0,\"a\":[2,1e3],\"b\":\" x\\\" \"}\n\\\n".to_vec()
+        );
+
+        let decoded = read_all(&stream, Framing::Ndjson);
+        assert_eq!(
+            decoded,
+            vec![
+                b"a".to_vec(),
+                Vec::new(),
+                b"{\"b\":1.0,\"a\":[2,1e3],\"b\":\" x\\\" \"}".to_vec(),
+                Vec::new(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frame_errors() {
+        let mut cut = Framing::LengthPrefixed.encode("hello").unwrap();
+        cut.truncate(6);
+        let result: Result<Vec<_>, _> =
+            FrameReader::new(cut.as_slice(), Framing::LengthPrefixed).collect();
+        assert!(matches!(
+            result,
+            Err(FrameError::Truncated {
+                expected: 9,
+                available: 6
+            })
+        ));
+
+        let mut decoder = FrameDecoder::new(Framing::OctetCounting);
+        decoder.extend(b"12x hello");
+        assert!(matches!(
+            decoder.next_frame(),
+            Err(FrameError::InvalidLength(_))
+        ));
+
+        // A hostile length prefix is rejected before anything is buffered for it
+        let mut decoder = FrameDecoder::new(Framing::LengthPrefixed);
+        decoder.extend(&[0xff, 0xff, 0xff, 0xff, b'x']);
+        assert!(matches!(
+            decoder.next_frame(),
+            Err(FrameError::FrameTooLarge {
+                len: 4_294_967_295,
+                max: DEFAULT_MAX_FRAME_LEN
+            })
+        ));
+
+        let stream = Framing::OctetCounting.encode_all(["hi", "hello"]).unwrap();
+        let result: Result<Vec<_>, _> = FrameReader::new(stream.as_slice(), Framing::OctetCounting)
+            .with_max_frame_len(4)
+            .collect();
+        assert!(matches!(
+            result,
+            Err(FrameError::FrameTooLarge { len: 5, max: 4 })
+        ));
+
+        let mut decoder = FrameDecoder::new(Framing::Ndjson).with_max_frame_len(4);
+        decoder.extend(b"hello");
+        assert!(matches!(
+            decoder.next_frame(),
+            Err(FrameError::FrameTooLarge { len: 5, max: 4 })
+        ));
+    }
+}
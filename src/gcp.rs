@@ -0,0 +1,172 @@
+use crate::utils::fields::value_to_text;
+use crate::utils::levels::syslog_severity;
+use crate::utils::time::record_time_or_now;
+use crate::{Format, FormatOptions, LogInfo};
+use chrono::SecondsFormat;
+use serde_json::{Map, Value};
+
+const SOURCE_LOCATION: &str = "logging.googleapis.com/sourceLocation";
+const TRACE: &str = "logging.googleapis.com/trace";
+const SPAN_ID: &str = "logging.googleapis.com/spanId";
+const TRACE_SAMPLED: &str = "logging.googleapis.com/trace_sampled";
+const LABELS: &str = "logging.googleapis.com/labels";
+
+/// Maps a level to a Cloud Logging `LogSeverity` name.
+fn severity(level: &str) -> &'static str {
+    match syslog_severity(level) {
+        Some(0) => "EMERGENCY",
+        Some(1) => "ALERT",
+        Some(2) => "CRITICAL",
+        Some(3) => "ERROR",
+        Some(4) => "WARNING",
+        Some(5) => "NOTICE",
+        Some(6) => "INFO",
+        Some(_) => "DEBUG",
+        None => "DEFAULT",
+    }
+}
+
+pub fn gcp() -> Format {
+    Format::new(|info: LogInfo, opts: FormatOptions| {
+        let project_id = opts.as_ref().and_then(|o| o.get("project_id"));
+
+        let mut meta = info.meta.clone();
+        meta.remove("level");
+        meta.remove("message");
+        meta.remove("splat");
+
+        let time = record_time_or_now(&info, "timestamp");
+        meta.remove("timestamp");
+
+        let mut entry = Map::new();
+
+        // Source location from the well-known `file`, `line` and `function` meta
+        let mut location = Map::new();
+        for key in ["file", "line", "function"] {
+            if let Some(value) = meta.remove(key) {
+                // `line` is an int64, which Cloud Logging expects as a string
+                location.insert(key.to_string(), Value::String(value_to_text(&value)));
+            }
+        }
+        if !location.is_empty() {
+            entry.insert(SOURCE_LOCATION.to_string(), Value::Object(location));
+        }
+
+        if let Some(trace_id) = meta.remove("trace_id") {
+            let trace_id = value_to_text(&trace_id);
+            let trace = match project_id {
+                Some(project) => format!("projects/{}/traces/{}", project, trace_id),
+                None => trace_id,
+            };
+            entry.insert(TRACE.to_string(), Value::String(trace));
+        }
+        if let Some(span_id) = meta.remove("span_id") {
+            entry.insert(SPAN_ID.to_string(), Value::String(value_to_text(&span_id)));
+        }
+        if let Some(sampled) = meta.remove("trace_sampled") {
+            entry.insert(TRACE_SAMPLED.to_string(), sampled);
+        }
+
+        // Labels must be a map of strings; anything else stays in jsonPayload
+        let labels = match meta.get("labels") {
+            Some(Value::Object(_)) => meta.remove("labels"),
+            _ => None,
+        };
+        if let Some(Value::Object(labels)) = labels {
+            let labels = labels
+                .into_iter()
+                .map(|(k, v)| (k, Value::String(value_to_text(&v))))
+                .collect();
+            entry.insert(LABELS.to_string(), Value::Object(labels));
+        }
+
+        if let Some(request) = meta
+            .remove("http_request")
+            .or_else(|| meta.remove("httpRequest"))
+        {
+            entry.insert("httpRequest".to_string(), request);
+        }
+
+        // Everything else ends up in jsonPayload
+        for (key, value) in meta {
+            entry.entry(key).or_insert(value);
+        }
+
+        entry.insert(
+            "severity".to_string(),
+            Value::String(severity(&info.level).to_string()),
+        );
+        entry.insert("message".to_string(), Value::String(info.message.clone()));
+        entry.insert(
+            "time".to_string(),
+            Value::String(time.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+        );
+
+        Some(LogInfo {
+            level: info.level,
+            message: Value::Object(entry).to_string(),
+            meta: info.meta,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_gcp_formatter() {
+        let formatter = gcp().with_option("project_id", "my-project");
+
+        let info = LogInfo::new("warn", "Slow request")
+            .add_meta("timestamp", "2024-08-27 02:39:15")
+            .add_meta("file", "src/main.rs")
+            .add_meta("line", 42)
+            .add_meta("trace_id", "105445aa7843bc8bf206b12000100000")
+            .add_meta("span_id", "000000000000004a")
+            .add_meta("labels", json!({"team": "payments", "shard": 3}))
+            .add_meta(
+                "http_request",
+                json!({"requestMethod": "GET", "status": 200}),
+            )
+            .add_meta("duration_ms", 1503);
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        let entry: Value = serde_json::from_str(&result.message).unwrap();
+        assert_eq!(
+            entry,
+            json!({
+                "severity": "WARNING",
+                "message": "Slow request",
+                "time": "2024-08-27T02:39:15Z",
+                "logging.googleapis.com/sourceLocation": {"file": "src/main.rs", "line": "42"},
+                "logging.googleapis.com/trace": "projects/my-project/traces/105445aa7843bc8bf206b12000100000",
+                "logging.googleapis.com/spanId": "000000000000004a",
+                "logging.googleapis.com/labels": {"team": "payments", "shard": "3"},
+                "httpRequest": {"requestMethod": "GET", "status": 200},
+                "duration_ms": 1503,
+            })
+        );
+    }
+
+    #[test]
+    fn test_gcp_invalid_labels() {
+        let info = LogInfo::new("info", "hi").add_meta("labels", "oops");
+
+        let result = gcp().transform(info, None).unwrap();
+        let entry: Value = serde_json::from_str(&result.message).unwrap();
+        assert_eq!(entry["labels"], "oops");
+        assert!(entry.get(LABELS).is_none());
+    }
+
+    #[test]
+    fn test_gcp_severity_mapping() {
+        assert_eq!(severity("error"), "ERROR");
+        assert_eq!(severity("silly"), "DEBUG");
+        assert_eq!(severity("emerg"), "EMERGENCY");
+        assert_eq!(severity("custom"), "DEFAULT");
+    }
+}
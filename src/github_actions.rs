@@ -0,0 +1,139 @@
+use crate::utils::fields::value_to_text;
+use crate::utils::levels::syslog_severity;
+use crate::{Format, FormatOptions, LogInfo};
+
+/// Meta keys mapped to workflow command properties, in output order.
+const PROPERTIES: [(&str, &str); 6] = [
+    ("file", "file"),
+    ("line", "line"),
+    ("end_line", "endLine"),
+    ("col", "col"),
+    ("end_col", "endColumn"),
+    ("title", "title"),
+];
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// The runner reads any output line starting with `::` as a workflow command, so in
+/// regular output the first colon of such lines is escaped to keep log text from
+/// running commands like `::add-mask::` or `::stop-commands::`.
+fn neutralize_commands(message: &str) -> String {
+    message
+        .split('\n')
+        .map(|line| {
+            let indent = line.len() - line.trim_start().len();
+            if line[indent..].starts_with("::") {
+                format!("{}%3A{}", &line[..indent], &line[indent + 1..])
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn github_actions() -> Format {
+    Format::new(|mut info: LogInfo, _opts: FormatOptions| {
+        let command = match syslog_severity(&info.level) {
+            Some(0..=3) => "error",
+            Some(4) => "warning",
+            Some(5) => "notice",
+            Some(7) => "debug",
+            // Other levels are printed as regular output
+            _ => {
+                info.message = neutralize_commands(&info.message);
+                return Some(info);
+            }
+        };
+
+        // `debug` doesn't take any properties
+        let properties: Vec<String> = if command == "debug" {
+            Vec::new()
+        } else {
+            PROPERTIES
+                .iter()
+                .filter_map(|(key, property)| {
+                    let value = value_to_text(info.meta.get(*key)?);
+                    Some(format!("{}={}", property, escape_property(&value)))
+                })
+                .collect()
+        };
+
+        info.message = if properties.is_empty() {
+            format!("::{}::{}", command, escape_data(&info.message))
+        } else {
+            format!(
+                "::{} {}::{}",
+                command,
+                properties.join(","),
+                escape_data(&info.message)
+            )
+        };
+        Some(info)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_actions_formatter() {
+        let formatter = github_actions();
+
+        let info = LogInfo::new("warn", "Unused import\n50% of files affected")
+            .add_meta("file", "src/main.rs")
+            .add_meta("line", 12)
+            .add_meta("col", 5)
+            .add_meta("title", "Lint: unused, import");
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        assert_eq!(
+            result.message,
+            "::warning file=src/main.rs,line=12,col=5,title=Lint%3A unused%2C import::Unused import%0A50%25 of files affected"
+        );
+    }
+
+    #[test]
+    fn test_github_actions_levels() {
+        let formatter = github_actions();
+
+        let error = LogInfo::new("error", "Build failed");
+        assert_eq!(
+            formatter.transform(error, None).unwrap().message,
+            "::error::Build failed"
+        );
+
+        let debug = LogInfo::new("debug", "cache hit").add_meta("file", "ignored.rs");
+        assert_eq!(
+            formatter.transform(debug, None).unwrap().message,
+            "::debug::cache hit"
+        );
+
+        let info = LogInfo::new("info", "Compiling");
+        assert_eq!(
+            formatter.transform(info, None).unwrap().message,
+            "Compiling"
+        );
+    }
+
+    #[test]
+    fn test_github_actions_neutralizes_commands() {
+        let info = LogInfo::new("info", "x\n::add-mask::secret\n  ::stop-commands::t\na::b");
+        assert_eq!(
+            github_actions().transform(info, None).unwrap().message,
+            "x\n%3A:add-mask::secret\n  %3A:stop-commands::t\na::b"
+        );
+    }
+}
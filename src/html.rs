@@ -0,0 +1,156 @@
+use crate::utils::levels::plain_level;
+use crate::{Format, FormatOptions, LogInfo};
+use serde_json::{Map, Value};
+
+const PAGE_STYLE: &str = "body{font-family:monospace}\
+.log{padding:2px 4px;border-left:4px solid #ccc}\
+.log-error,.log-crit,.log-alert,.log-emerg{border-color:#d33}\
+.log-warn,.log-warning{border-color:#e90}\
+.log-info{border-color:#39c}\
+.log-debug,.log-verbose,.log-silly{border-color:#999}\
+.level{font-weight:bold}\
+details pre{margin:0 0 0 1em}";
+
+/// Escapes the characters that are significant in HTML text and attribute values.
+pub(crate) fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+/// The plain level as a CSS class name, e.g. `log-error`. Anything that isn't
+/// valid in a class name is dropped.
+fn level_class(level: &str) -> String {
+    let name: String = level
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    format!("log-{}", name)
+}
+
+/// Meta shown in the `<details>` block, without the internal `splat` key.
+pub(crate) fn visible_meta(info: &LogInfo) -> Map<String, Value> {
+    info.meta
+        .iter()
+        .filter(|(k, _)| k.as_str() != "splat")
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// Renders the log info as an HTML fragment: a `<div>` with level-based CSS classes
+/// and, when there is meta, a collapsible `<details>` block with the meta as JSON.
+pub fn html() -> Format {
+    Format::new(|mut info: LogInfo, opts: FormatOptions| {
+        let open = opts
+            .as_ref()
+            .and_then(|o| o.get("open"))
+            .is_some_and(|v| v == "true");
+
+        let level = plain_level(&info.level);
+        let mut fragment = format!(
+            "<div class=\"log {}\"><span class=\"level\">{}</span> <span class=\"message\">{}</span>",
+            level_class(&level),
+            escape_html(&level),
+            escape_html(&info.message)
+        );
+
+        let meta = visible_meta(&info);
+        if !meta.is_empty() {
+            let json = serde_json::to_string_pretty(&meta).unwrap_or_default();
+            fragment.push_str(&format!(
+                "<details{}><summary>meta</summary><pre>{}</pre></details>",
+                if open { " open" } else { "" },
+                escape_html(&json)
+            ));
+        }
+        fragment.push_str("</div>");
+
+        info.message = fragment;
+        Some(info)
+    })
+}
+
+/// Renders the records with the given format (usually `html()`) and wraps them in
+/// a complete HTML document with a default stylesheet.
+pub fn html_page(records: &[LogInfo], fragment_format: &Format, opts: FormatOptions) -> String {
+    let title = opts
+        .as_ref()
+        .and_then(|o| o.get("title"))
+        .map_or("Logs", String::as_str);
+
+    let body: Vec<String> = records
+        .iter()
+        .filter_map(|record| fragment_format.transform(record.clone(), None))
+        .map(|info| info.message)
+        .collect();
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}\n</body>\n</html>\n",
+        escape_html(title),
+        PAGE_STYLE,
+        body.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colorize;
+
+    #[test]
+    fn test_html_formatter() {
+        let formatter = html();
+
+        let info =
+            LogInfo::new("error", "<script>alert('x')</script>").add_meta("user", "bob & alice");
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        assert_eq!(
+            result.message,
+            "<div class=\"log log-error\"><span class=\"level\">error</span> \
+             <span class=\"message\">&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;</span>\
+             <details><summary>meta</summary><pre>{\n  &quot;user&quot;: &quot;bob &amp; alice&quot;\n}</pre></details></div>"
+        );
+
+        let colored = colorize()
+            .transform(LogInfo::new("info", "ready"), None)
+            .unwrap();
+        assert_eq!(
+            formatter.transform(colored, None).unwrap().message,
+            "<div class=\"log log-info\"><span class=\"level\">info</span> <span class=\"message\">ready</span></div>"
+        );
+    }
+
+    #[test]
+    fn test_html_page() {
+        let records = vec![
+            LogInfo::new("info", "deploy started"),
+            LogInfo::new("warn", "slow migration").add_meta("ms", 5000),
+        ];
+
+        let page = html_page(
+            &records,
+            &html().with_option("open", "true"),
+            Some([("title".to_string(), "Incident <42>".to_string())].into()),
+        );
+
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<title>Incident &lt;42&gt;</title>"));
+        assert!(page.contains(
+            "<span class=\"message\">deploy started</span></div>\n<div class=\"log log-warn\">"
+        ));
+        assert!(page.contains("<details open><summary>meta</summary>"));
+        assert!(page.ends_with("</body>\n</html>\n"));
+    }
+}
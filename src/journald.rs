@@ -0,0 +1,125 @@
+use crate::utils::fields::value_to_text;
+use crate::utils::host::process_name;
+use crate::utils::levels::syslog_severity;
+use crate::{FormatOptions, LogInfo};
+
+/// Turns a meta key into a valid journal field name: uppercase ASCII letters,
+/// digits and underscores, not starting with an underscore (reserved for trusted
+/// fields) or a digit, at most 64 characters.
+fn field_name(key: &str) -> Option<String> {
+    let name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let name = name.trim_start_matches(|c: char| c == '_' || c.is_ascii_digit());
+    let name: String = name.chars().take(64).collect();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+fn write_field(datagram: &mut Vec<u8>, name: &str, value: &str) {
+    datagram.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        // Values with newlines use the binary-safe form: NAME\n<u64 LE length><value>\n
+        datagram.push(b'\n');
+        datagram.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        datagram.push(b'=');
+    }
+    datagram.extend_from_slice(value.as_bytes());
+    datagram.push(b'\n');
+}
+
+/// Encodes a log info in the systemd journal native protocol, ready to be sent as
+/// a datagram to `/run/systemd/journal/socket`.
+///
+/// `MESSAGE` and `PRIORITY` (from the level) are always written, along with
+/// `SYSLOG_IDENTIFIER` (the `identifier` option, or the executable name). Meta keys
+/// become uppercase field names, with strings written as-is and other values as JSON.
+pub fn journald(info: &LogInfo, opts: FormatOptions) -> Vec<u8> {
+    let identifier = opts
+        .as_ref()
+        .and_then(|o| o.get("identifier"))
+        .map_or(process_name(), String::as_str);
+
+    let mut datagram = Vec::new();
+    write_field(&mut datagram, "MESSAGE", &info.message);
+    write_field(
+        &mut datagram,
+        "PRIORITY",
+        &syslog_severity(&info.level).unwrap_or(6).to_string(),
+    );
+    write_field(&mut datagram, "SYSLOG_IDENTIFIER", identifier);
+
+    let mut keys: Vec<&String> = info.meta.keys().collect();
+    keys.sort();
+
+    for key in keys {
+        let Some(name) = field_name(key) else {
+            continue;
+        };
+        if matches!(name.as_str(), "MESSAGE" | "PRIORITY" | "SYSLOG_IDENTIFIER") {
+            continue;
+        }
+        write_field(&mut datagram, &name, &value_to_text(&info.meta[key]));
+    }
+
+    datagram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_journald_field_names() {
+        assert_eq!(field_name("user_id").as_deref(), Some("USER_ID"));
+        assert_eq!(field_name("http.status").as_deref(), Some("HTTP_STATUS"));
+        assert_eq!(field_name("_SYSTEMD_UNIT").as_deref(), Some("SYSTEMD_UNIT"));
+        assert_eq!(field_name("1st").as_deref(), Some("ST"));
+        assert_eq!(field_name("__").as_deref(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_journald_over_unix_socket() {
+        use std::os::unix::net::UnixDatagram;
+
+        // Stands in for /run/systemd/journal/socket
+        let dir = std::env::temp_dir().join(format!("logform-journald-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("socket");
+        let _ = std::fs::remove_file(&path);
+        let journal = UnixDatagram::bind(&path).unwrap();
+
+        let info = LogInfo::new("error", "Payment failed\nretrying")
+            .add_meta("order_id", "A-1")
+            .add_meta("attempt", 2);
+        let opts = HashMap::from([("identifier".to_string(), "checkout".to_string())]);
+
+        let client = UnixDatagram::unbound().unwrap();
+        client.send_to(&journald(&info, Some(opts)), &path).unwrap();
+
+        let mut buf = [0u8; 1024];
+        let len = journal.recv(&mut buf).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut expected = b"MESSAGE\n".to_vec();
+        expected.extend_from_slice(&23u64.to_le_bytes());
+        expected.extend_from_slice(b"Payment failed\nretrying\n");
+        expected.extend_from_slice(
+            b"PRIORITY=3\nSYSLOG_IDENTIFIER=checkout\nATTEMPT=2\nORDER_ID=A-1\n",
+        );
+        assert_eq!(&buf[..len], expected.as_slice());
+    }
+}
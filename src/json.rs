@@ -1,21 +1,214 @@
-use crate::{Format, FormatOptions, LogInfo};
+use crate::utils::safe_json::SafeLimits;
+use crate::{BatchFormat, Format, FormatOptions, LogInfo};
+use serde::Serialize;
+use serde_json::ser::{PrettyFormatter, Serializer};
 use serde_json::{Map, Value};
+use std::sync::Arc;
 
-pub fn json() -> Format {
-    Format::new(|info: LogInfo, _opts: FormatOptions| {
-        // Create a JSON object including the level, message, and other meta data
+/// Called for every key and value written, see `json_with_replacer`.
+pub(crate) type Replacer = Arc<dyn Fn(&str, Value) -> Option<Value> + Send + Sync>;
+
+/// What to do when a meta key has the same name as a core key.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Collision {
+    MetaWins,
+    CoreWins,
+    Prefix,
+}
+
+#[derive(Clone)]
+pub(crate) struct JsonOptions {
+    pub level_key: String,
+    pub message_key: String,
+    pub meta_key: Option<String>,
+    pub collision: Collision,
+    pub collision_prefix: String,
+    pub indent: Option<String>,
+    pub limits: Option<SafeLimits>,
+    pub replacer: Option<Replacer>,
+}
+
+impl JsonOptions {
+    pub fn from(opts: &FormatOptions) -> Self {
+        let get = |key: &str| opts.as_ref().and_then(|o| o.get(key));
+
+        let collision = match get("collision").map(String::as_str) {
+            Some("core") => Collision::CoreWins,
+            Some("prefix") => Collision::Prefix,
+            _ => Collision::MetaWins,
+        };
+
+        // Like `JSON.stringify`, `space` is either a number of spaces or the indent itself
+        let indent = get("space").and_then(|space| match space.parse::<usize>() {
+            Ok(0) => None,
+            Ok(n) => Some(" ".repeat(n.min(10))),
+            Err(_) if space.is_empty() => None,
+            Err(_) => Some(space.clone()),
+        });
+
+        let level_key = get("level_key").cloned().unwrap_or_else(|| "level".into());
+        let message_key = get("message_key")
+            .cloned()
+            .unwrap_or_else(|| "message".into());
+
+        let collision_prefix = get("collision_prefix")
+            .filter(|p| !p.is_empty())
+            .cloned()
+            .unwrap_or_else(|| "meta_".into());
+
+        // Nesting the meta under a core key would replace that core field, so such
+        // a key gets the collision prefix, like a colliding meta key
+        let meta_key = get("meta_key").filter(|k| !k.is_empty()).map(|k| {
+            let mut key = k.clone();
+            while key == level_key || key == message_key {
+                key.insert_str(0, &collision_prefix);
+            }
+            key
+        });
+
+        JsonOptions {
+            level_key,
+            message_key,
+            meta_key,
+            collision,
+            collision_prefix,
+            indent,
+            limits: SafeLimits::from(opts),
+            replacer: None,
+        }
+    }
+
+    /// Builds the JSON object for a log info, placing the meta according to the options.
+    pub fn build_object(&self, info: &LogInfo) -> Map<String, Value> {
         let mut log_object = Map::new();
 
-        log_object.insert("level".to_string(), Value::String(info.level.clone()));
-        log_object.insert("message".to_string(), Value::String(info.message.clone()));
+        log_object.insert(self.level_key.clone(), Value::String(info.level.clone()));
+        log_object.insert(
+            self.message_key.clone(),
+            Value::String(info.message.clone()),
+        );
 
-        // Include other meta information
+        if let Some(meta_key) = &self.meta_key {
+            let meta: Map<String, Value> = info
+                .meta
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            log_object.insert(meta_key.clone(), Value::Object(meta));
+            return self.finish(log_object);
+        }
+
+        let mut colliding = Vec::new();
         for (key, value) in &info.meta {
-            log_object.insert(key.clone(), value.clone());
+            let is_core = *key == self.level_key || *key == self.message_key;
+            match (is_core, self.collision) {
+                (true, Collision::CoreWins) => {}
+                (true, Collision::Prefix) => colliding.push((key, value)),
+                _ => {
+                    log_object.insert(key.clone(), value.clone());
+                }
+            }
+        }
+
+        // Renamed keys go in last and never replace a key that is already there;
+        // sorted so the result doesn't depend on the meta's iteration order
+        colliding.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, value) in colliding {
+            let mut renamed = format!("{}{}", self.collision_prefix, key);
+            while log_object.contains_key(&renamed) {
+                renamed.insert_str(0, &self.collision_prefix);
+            }
+            log_object.insert(renamed, value.clone());
+        }
+
+        self.finish(log_object)
+    }
+
+    /// Runs the replacer, then applies the safe mode limits, if any, measuring size
+    /// on the serialized output.
+    fn finish(&self, log_object: Map<String, Value>) -> Map<String, Value> {
+        let log_object = match &self.replacer {
+            Some(replacer) => replace_fields(log_object, replacer),
+            None => log_object,
+        };
+        match &self.limits {
+            Some(limits) => limits.apply(log_object, &self.level_key, &self.message_key, |o| {
+                self.serialize(&Value::Object(o.clone()))
+            }),
+            None => log_object,
+        }
+    }
+
+    /// Serializes a value, indented when `space` was given.
+    pub fn serialize(&self, value: &Value) -> String {
+        match &self.indent {
+            None => value.to_string(),
+            Some(indent) => {
+                let mut out = Vec::new();
+                let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+                let mut serializer = Serializer::with_formatter(&mut out, formatter);
+                value
+                    .serialize(&mut serializer)
+                    .expect("serializing a JSON value cannot fail");
+                String::from_utf8(out).expect("serde_json always emits valid UTF-8")
+            }
         }
+    }
+}
+
+/// Passes each field of an object through the replacer, nested values first.
+fn replace_fields(object: Map<String, Value>, replacer: &Replacer) -> Map<String, Value> {
+    object
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let value = replacer(&key, replace_nested(value, replacer))?;
+            Some((key, value))
+        })
+        .collect()
+}
+
+fn replace_nested(value: Value, replacer: &Replacer) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(replace_fields(map, replacer)),
+        // Like `JSON.stringify`, array items are passed with their index as the key
+        // and written as null when the replacer leaves them out
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    replacer(&i.to_string(), replace_nested(item, replacer)).unwrap_or(Value::Null)
+                })
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+pub fn json() -> Format {
+    json_format(None)
+}
+
+/// Like `json()`, with a replacer in the manner of JS `JSON.stringify`: it is called
+/// with every key and value, nested ones included, and returns the value to write,
+/// or `None` to leave the key out.
+pub fn json_with_replacer<R>(replacer: R) -> Format
+where
+    R: Fn(&str, Value) -> Option<Value> + Send + Sync + 'static,
+{
+    json_format(Some(Arc::new(replacer)))
+}
+
+fn json_format(replacer: Option<Replacer>) -> Format {
+    Format::new(move |info: LogInfo, opts: FormatOptions| {
+        let mut options = JsonOptions::from(&opts);
+        options.replacer = replacer.clone();
+
+        // Create a JSON object including the level, message, and other meta data
+        let log_object = options.build_object(&info);
 
         // Convert the log object to a JSON string
-        let json_message = Value::Object(log_object).to_string();
+        let json_message = options.serialize(&Value::Object(log_object));
 
         // Return a new LogInfo object with the JSON message
         Some(LogInfo {
@@ -26,6 +219,35 @@ pub fn json() -> Format {
     })
 }
 
+/// Renders a batch of log infos as a JSON array, or with `envelope` as an object
+/// holding the array under that key (e.g. `{"logs":[...]}`), with the static fields
+/// from `envelope_fields` (a JSON object) alongside. Records accept the same options
+/// as `json()`.
+pub fn json_array() -> BatchFormat {
+    BatchFormat::new(|infos: Vec<LogInfo>, opts: FormatOptions| {
+        let options = JsonOptions::from(&opts);
+        let get = |key: &str| opts.as_ref().and_then(|o| o.get(key));
+
+        let records: Vec<Value> = infos
+            .iter()
+            .map(|info| Value::Object(options.build_object(info)))
+            .collect();
+
+        let body = match get("envelope").filter(|k| !k.is_empty()) {
+            Some(key) => {
+                let mut envelope: Map<String, Value> = get("envelope_fields")
+                    .and_then(|raw| serde_json::from_str(raw).ok())
+                    .unwrap_or_default();
+                envelope.insert(key.clone(), Value::Array(records));
+                Value::Object(envelope)
+            }
+            None => Value::Array(records),
+        };
+
+        Some(options.serialize(&body))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,4 +266,156 @@ mod tests {
         // The output should be a JSON string like:
         // {"level":"info","message":"User logged in","user_id":12345,"session_id":"abcde12345"}
     }
+
+    #[test]
+    fn test_json_key_options() {
+        let info = LogInfo::new("info", "User logged in")
+            .add_meta("msg", "from meta")
+            .add_meta("user_id", 12345);
+
+        let renamed = json()
+            .with_option("level_key", "severity")
+            .with_option("message_key", "msg");
+
+        let result = renamed.transform(info.clone(), None).unwrap();
+        assert_eq!(
+            result.message,
+            r#"{"msg":"from meta","severity":"info","user_id":12345}"#
+        );
+
+        let core_wins = renamed.clone().with_option("collision", "core");
+        let result = core_wins.transform(info.clone(), None).unwrap();
+        assert_eq!(
+            result.message,
+            r#"{"msg":"User logged in","severity":"info","user_id":12345}"#
+        );
+
+        let prefixed = renamed.with_option("collision", "prefix");
+        let result = prefixed.transform(info.clone(), None).unwrap();
+        assert_eq!(
+            result.message,
+            r#"{"meta_msg":"from meta","msg":"User logged in","severity":"info","user_id":12345}"#
+        );
+
+        let nested = json().with_option("meta_key", "meta");
+        let result = nested.transform(info, None).unwrap();
+        assert_eq!(
+            result.message,
+            r#"{"level":"info","message":"User logged in","meta":{"msg":"from meta","user_id":12345}}"#
+        );
+    }
+
+    #[test]
+    fn test_json_collision_prefix() {
+        let info = LogInfo::new("info", "hi")
+            .add_meta("message", "x")
+            .add_meta("meta_message", "y");
+
+        let result = json()
+            .with_option("collision", "prefix")
+            .transform(info.clone(), None)
+            .unwrap();
+        assert_eq!(
+            result.message,
+            r#"{"level":"info","message":"hi","meta_message":"y","meta_meta_message":"x"}"#
+        );
+
+        // A `meta_key` naming a core key gets the collision prefix
+        let result = json()
+            .with_option("meta_key", "message")
+            .transform(info, None)
+            .unwrap();
+        assert_eq!(
+            result.message,
+            r#"{"level":"info","message":"hi","meta_message":{"message":"x","meta_message":"y"}}"#
+        );
+    }
+
+    #[test]
+    fn test_json_replacer() {
+        let info = LogInfo::new("info", "login")
+            .add_meta("password", "hunter2")
+            .add_meta("user", serde_json::json!({"name": "bob", "ids": [1, 2, 3]}));
+
+        let format = json_with_replacer(|key, value| match (key, value) {
+            ("password", _) => None,
+            (_, Value::Number(n)) if n.as_u64() == Some(2) => None,
+            (_, Value::String(s)) if key == "name" => Some(Value::String(s.to_uppercase())),
+            (_, value) => Some(value),
+        });
+        let result = format.transform(info, None).unwrap();
+        assert_eq!(
+            result.message,
+            r#"{"level":"info","message":"login","user":{"ids":[1,null,3],"name":"BOB"}}"#
+        );
+    }
+
+    #[test]
+    fn test_json_space_option() {
+        let info = LogInfo::new("info", "hi").add_meta("tags", vec!["a"]);
+
+        let result = json()
+            .with_option("space", "2")
+            .transform(info.clone(), None)
+            .unwrap();
+        assert_eq!(
+            result.message,
+            "{\n  \"level\": \"info\",\n  \"message\": \"hi\",\n  \"tags\": [\n    \"a\"\n  ]\n}"
+        );
+
+        let result = json()
+            .with_option("space", "\t")
+            .transform(info, None)
+            .unwrap();
+        assert!(result.message.starts_with("{\n\t\"level\""));
+    }
+
+    #[test]
+    fn test_json_safe_mode() {
+        let info = LogInfo::new("info", "hi")
+            .add_meta("deep", serde_json::json!({"a": {"b": {"c": {}}}}))
+            .add_meta("blob", "x".repeat(100_000));
+
+        let result = json()
+            .with_option("safe", "true")
+            .with_option("max_depth", "3")
+            .transform(info, None)
+            .unwrap();
+
+        let output: Value = serde_json::from_str(&result.message).unwrap();
+        assert_eq!(output["deep"]["a"]["b"], "[Truncated: object with 1 keys]");
+        assert!(output["blob"]
+            .as_str()
+            .unwrap()
+            .ends_with("...[Truncated: 91808 more chars]"));
+    }
+
+    #[test]
+    fn test_json_array() {
+        let infos = vec![
+            LogInfo::new("info", "first").add_meta("n", 1),
+            LogInfo::new("warn", "second"),
+        ];
+
+        let result = json_array().transform_batch(infos.clone(), None).unwrap();
+        assert_eq!(
+            result,
+            r#"[{"level":"info","message":"first","n":1},{"level":"warn","message":"second"}]"#
+        );
+
+        let enveloped = json_array()
+            .with_option("envelope", "logs")
+            .with_option("envelope_fields", r#"{"service": "api"}"#)
+            .with_option("level_key", "severity");
+        let result = enveloped.transform_batch(infos, None).unwrap();
+        assert_eq!(
+            result,
+            r#"{"logs":[{"message":"first","n":1,"severity":"info"},{"message":"second","severity":"warn"}],"service":"api"}"#
+        );
+
+        assert_eq!(
+            json_array().transform_batch(Vec::new(), None).unwrap(),
+            "[]"
+        );
+    }
 }
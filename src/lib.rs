@@ -1,26 +1,95 @@
+mod access_log;
 mod align;
+mod batch;
+mod bunyan;
+#[cfg(feature = "cbor")]
+mod cbor;
 mod colorize;
 mod combine;
+mod csv;
+mod elasticsearch;
+mod emf;
+mod error;
+#[cfg(feature = "msgpack")]
+mod fluent;
 mod format;
+mod framing;
+mod gcp;
+mod github_actions;
+mod html;
+mod journald;
 mod json;
 mod log_info;
+mod logfmt;
+mod loki;
+mod markdown;
 mod ms;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+mod otlp;
+pub mod parse;
+mod pino;
 mod pretty_print;
 mod printf;
+#[cfg(feature = "protobuf")]
+mod protobuf;
+mod sd_prefix;
+mod sentry;
+mod siem;
 mod simple;
+mod splunk;
 mod timestamp;
+#[cfg(feature = "toml")]
+mod toml;
 mod uncolorize;
 mod utils;
+mod w3c;
+#[cfg(feature = "yaml")]
+mod yaml;
 
+pub use access_log::access_log;
 pub use align::align;
+pub use batch::{split_batches, BatchFormat};
+pub use bunyan::bunyan;
+#[cfg(feature = "cbor")]
+pub use cbor::{cbor, decode_cbor, encode_cbor};
 pub use colorize::colorize;
 pub use combine::combine;
+pub use csv::{csv, csv_header};
+pub use elasticsearch::elasticsearch_bulk;
+pub use emf::{emf, emf_document, EmfError};
+pub use error::{DecodeError, ParseError};
+#[cfg(feature = "msgpack")]
+pub use fluent::{fluent_ack, fluent_forward, ForwardMessage};
 pub use format::{Format, FormatOptions};
-pub use json::json;
+pub use framing::{FrameDecoder, FrameError, FrameReader, Framing};
+pub use gcp::gcp;
+pub use github_actions::github_actions;
+pub use html::{html, html_page};
+pub use journald::journald;
+pub use json::{json, json_array, json_with_replacer};
 pub use log_info::LogInfo;
+pub use logfmt::logfmt;
+pub use loki::loki;
+pub use markdown::{markdown, markdown_table};
 pub use ms::ms;
+#[cfg(feature = "msgpack")]
+pub use msgpack::{decode_msgpack, encode_msgpack, msgpack};
+pub use otlp::{otlp_json, otlp_resource_logs};
+pub use pino::pino;
 pub use pretty_print::pretty_print;
 pub use printf::printf;
+#[cfg(feature = "protobuf")]
+pub use protobuf::{decode_protobuf, encode_protobuf, protobuf, PROTOBUF_SCHEMA};
+pub use sd_prefix::sd_prefix;
+pub use sentry::{error_chain, sentry_event};
+pub use siem::{cef, leef};
 pub use simple::simple;
+pub use splunk::{splunk_hec, splunk_hec_batch};
 pub use timestamp::timestamp;
+#[cfg(feature = "toml")]
+pub use toml::toml;
 pub use uncolorize::uncolorize;
+pub use w3c::{w3c_extended, w3c_extended_header};
+#[cfg(feature = "yaml")]
+pub use yaml::yaml;
@@ -0,0 +1,139 @@
+use crate::{Format, FormatOptions, LogInfo};
+use serde_json::Value;
+
+/// Quotes a logfmt value when it is empty or contains spaces, `=`, quotes or
+/// control characters.
+pub(crate) fn logfmt_value(value: &str) -> String {
+    let needs_quoting = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == ' ' || c == '=' || c == '"' || c.is_control());
+
+    if !needs_quoting {
+        return value.to_string();
+    }
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Quotes a string meta value that would otherwise read as a number, boolean, null,
+/// array or object, e.g. `id="12345"`.
+fn logfmt_string(value: &str) -> String {
+    let quoted = logfmt_value(value);
+    if !quoted.starts_with('"') && serde_json::from_str::<Value>(value).is_ok() {
+        format!("\"{}\"", quoted)
+    } else {
+        quoted
+    }
+}
+
+/// Keys can't contain spaces, `=`, quotes or control characters; those are replaced
+/// with `_`.
+fn logfmt_key(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c == ' ' || c == '=' || c == '"' || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+pub fn logfmt() -> Format {
+    Format::new(|info: LogInfo, _opts: FormatOptions| {
+        let mut pairs = vec![
+            format!("level={}", logfmt_value(&info.level)),
+            format!("msg={}", logfmt_value(&info.message)),
+        ];
+
+        // Sort the meta so lines are stable
+        let mut keys: Vec<&String> = info.meta.keys().collect();
+        keys.sort();
+
+        let mut written: Vec<String> = vec!["level".to_string(), "msg".to_string()];
+        for key in keys {
+            if key == "splat" || key.is_empty() {
+                continue;
+            }
+            // A sanitized key never replaces a key that is already written or in use
+            let name = logfmt_key(key);
+            if written.contains(&name) || (name != *key && info.meta.contains_key(&name)) {
+                continue;
+            }
+
+            let value = match &info.meta[key] {
+                Value::Null => String::new(),
+                Value::String(s) => logfmt_string(s),
+                other => logfmt_value(&other.to_string()),
+            };
+            pairs.push(format!("{}={}", name, value));
+            written.push(name);
+        }
+
+        Some(LogInfo {
+            level: info.level,
+            message: pairs.join(" "),
+            meta: info.meta,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_logfmt_formatter() {
+        let formatter = logfmt();
+
+        let info = LogInfo::new("info", "User logged in")
+            .add_meta("user_id", 12345)
+            .add_meta("path", "/login")
+            .add_meta("agent", "curl/8.0 \"beta\"")
+            .add_meta("tags", json!(["a", "b"]))
+            .add_meta("empty", "")
+            .add_meta("missing", json!(null));
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        assert_eq!(
+            result.message,
+            r#"level=info msg="User logged in" agent="curl/8.0 \"beta\"" empty="" missing= path=/login tags="[\"a\",\"b\"]" user_id=12345"#
+        );
+    }
+
+    #[test]
+    fn test_logfmt_keys_and_json_like_strings() {
+        let info = LogInfo::new("info", "hi")
+            .add_meta("user name", "bob")
+            .add_meta("a=b", "c")
+            .add_meta("a_b", "kept")
+            .add_meta("id", "12345")
+            .add_meta("flag", "true")
+            .add_meta("list", "[1]")
+            .add_meta("count", 3);
+
+        let result = logfmt().transform(info, None).unwrap();
+        assert_eq!(
+            result.message,
+            r#"level=info msg=hi a_b=kept count=3 flag="true" id="12345" list="[1]" user_name=bob"#
+        );
+    }
+}
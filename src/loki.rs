@@ -0,0 +1,149 @@
+use crate::utils::fields::value_to_text;
+use crate::utils::time::{record_time_or_now, unix_nanos};
+use crate::{Format, FormatOptions, LogInfo};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Loki label names must match `[a-zA-Z_][a-zA-Z0-9_]*`.
+fn label_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// Encodes a batch of records as a Loki push API body:
+/// `{"streams":[{"stream":{...},"values":[["<ns>","<line>"], ...]}]}`.
+///
+/// Records are grouped into streams by the `labels` option (comma-separated meta
+/// keys, `level` for the level) plus any `static_labels` (a JSON object), and each
+/// line is rendered with `line_format`. Records that `line_format` filters out are
+/// skipped. Values are sorted by timestamp within each stream, as Loki requires.
+pub fn loki(records: &[LogInfo], line_format: &Format, opts: FormatOptions) -> String {
+    let opt = |key: &str| opts.as_ref().and_then(|o| o.get(key));
+
+    let label_keys: Vec<&str> = opt("labels")
+        .map(|labels| {
+            labels
+                .split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let static_labels: HashMap<String, Value> = opt("static_labels")
+        .and_then(|raw| serde_json::from_str(raw).ok())
+        .unwrap_or_default();
+
+    let timestamp_key = opt("timestamp_key").map_or("timestamp", String::as_str);
+
+    // Streams are keyed by their sorted label set so the output is stable
+    let mut streams: BTreeMap<BTreeMap<String, String>, Vec<(u64, String)>> = BTreeMap::new();
+
+    for record in records {
+        let mut labels: BTreeMap<String, String> = static_labels
+            .iter()
+            .map(|(k, v)| (label_name(k), value_to_text(v)))
+            .collect();
+
+        for key in &label_keys {
+            let value = match *key {
+                "level" => Some(record.level.clone()),
+                _ => record.meta.get(*key).map(value_to_text),
+            };
+            if let Some(value) = value {
+                labels.insert(label_name(key), value);
+            }
+        }
+
+        let time = unix_nanos(&record_time_or_now(record, timestamp_key));
+        let Some(line) = line_format.transform(record.clone(), None) else {
+            continue;
+        };
+
+        streams
+            .entry(labels)
+            .or_default()
+            .push((time, line.message));
+    }
+
+    let streams: Vec<Value> = streams
+        .into_iter()
+        .map(|(labels, mut values)| {
+            values.sort_by_key(|(time, _)| *time);
+            let stream: Map<String, Value> = labels
+                .into_iter()
+                .map(|(k, v)| (k, Value::String(v)))
+                .collect();
+            let values: Vec<Value> = values
+                .into_iter()
+                .map(|(time, line)| json!([time.to_string(), line]))
+                .collect();
+            json!({ "stream": stream, "values": values })
+        })
+        .collect();
+
+    json!({ "streams": streams }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logfmt;
+
+    #[test]
+    fn test_loki_encoder() {
+        let records = vec![
+            LogInfo::new("info", "second")
+                .add_meta("timestamp", "2024-08-27T02:39:16Z")
+                .add_meta("service", "api"),
+            LogInfo::new("error", "boom")
+                .add_meta("timestamp", "2024-08-27T02:39:17Z")
+                .add_meta("service", "api"),
+            LogInfo::new("info", "first")
+                .add_meta("timestamp", "2024-08-27T02:39:15Z")
+                .add_meta("service", "api"),
+        ];
+
+        let opts = HashMap::from([
+            ("labels".to_string(), "service,level".to_string()),
+            (
+                "static_labels".to_string(),
+                r#"{"job": "checkout"}"#.to_string(),
+            ),
+        ]);
+        let body = loki(&records, &logfmt(), Some(opts));
+        println!("{}", body);
+
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({"streams": [
+                {
+                    "stream": {"job": "checkout", "level": "error", "service": "api"},
+                    "values": [
+                        ["1724726357000000000", "level=error msg=boom service=api timestamp=2024-08-27T02:39:17Z"],
+                    ],
+                },
+                {
+                    "stream": {"job": "checkout", "level": "info", "service": "api"},
+                    "values": [
+                        ["1724726355000000000", "level=info msg=first service=api timestamp=2024-08-27T02:39:15Z"],
+                        ["1724726356000000000", "level=info msg=second service=api timestamp=2024-08-27T02:39:16Z"],
+                    ],
+                },
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_loki_label_names() {
+        assert_eq!(label_name("http.status"), "http_status");
+        assert_eq!(label_name("5xx"), "_5xx");
+    }
+}
@@ -0,0 +1,173 @@
+use crate::html::visible_meta;
+use crate::utils::levels::plain_level;
+use crate::{Format, FormatOptions, LogInfo};
+use std::collections::HashMap;
+
+/// Table cells can't contain pipes or line breaks.
+fn escape_cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\r', '\n'], "<br>")
+}
+
+fn longest_backtick_run(value: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in value.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    longest
+}
+
+/// Wraps text in an inline code span, using a backtick run longer than any inside it.
+fn code_span(value: &str) -> String {
+    let fence = "`".repeat(longest_backtick_run(value) + 1);
+    if value.starts_with('`') || value.ends_with('`') {
+        format!("{} {} {}", fence, value, fence)
+    } else {
+        format!("{}{}{}", fence, value, fence)
+    }
+}
+
+fn table_row(info: &LogInfo) -> String {
+    let meta = visible_meta(info);
+    // Code spans don't process backslash escapes, so only the pipe is escaped inside one
+    let meta_cell = if meta.is_empty() {
+        String::new()
+    } else {
+        code_span(&serde_json::to_string(&meta).unwrap_or_default()).replace('|', "\\|")
+    };
+
+    format!(
+        "| {} | {} | {} |",
+        escape_cell(&plain_level(&info.level)),
+        escape_cell(&info.message),
+        meta_cell
+    )
+}
+
+fn list_item(info: &LogInfo) -> String {
+    // Continuation lines are indented so they stay part of the list item
+    let message = info.message.replace('\n', "\n  ");
+    let mut item = format!("- **{}**: {}", plain_level(&info.level), message);
+
+    let meta = visible_meta(info);
+    if !meta.is_empty() {
+        let json = serde_json::to_string_pretty(&meta).unwrap_or_default();
+        let fence = "`".repeat((longest_backtick_run(&json) + 1).max(3));
+        item.push_str(&format!("\n\n  {}json\n", fence));
+        for line in json.lines() {
+            item.push_str(&format!("  {}\n", line));
+        }
+        item.push_str(&format!("  {}", fence));
+    }
+    item
+}
+
+/// Renders the log info as Markdown, either as a table row (`| level | message | meta |`,
+/// the default) or with `style=list` as a bullet with the meta in a fenced JSON block.
+pub fn markdown() -> Format {
+    Format::new(|mut info: LogInfo, opts: FormatOptions| {
+        let style = opts
+            .as_ref()
+            .and_then(|o| o.get("style"))
+            .map_or("table", String::as_str);
+
+        info.message = match style {
+            "list" => list_item(&info),
+            _ => table_row(&info),
+        };
+        Some(info)
+    })
+}
+
+/// Renders the records as table rows with the given `markdown()` format and adds
+/// the header and delimiter rows, giving a complete Markdown table.
+pub fn markdown_table(records: &[LogInfo], row_format: &Format) -> String {
+    let table_style = Some(HashMap::from([("style".to_string(), "table".to_string())]));
+
+    let mut lines = vec![
+        "| Level | Message | Meta |".to_string(),
+        "| --- | --- | --- |".to_string(),
+    ];
+    lines.extend(
+        records
+            .iter()
+            .filter_map(|record| row_format.transform(record.clone(), table_style.clone()))
+            .map(|info| info.message),
+    );
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_markdown_formatter() {
+        let formatter = markdown();
+
+        let info = LogInfo::new("error", "exit code 1 | retrying\nsecond try")
+            .add_meta("cmd", "echo `date`");
+
+        let result = formatter.transform(info.clone(), None).unwrap();
+        println!("{}", result.message);
+        assert_eq!(
+            result.message,
+            r#"| error | exit code 1 \| retrying<br>second try | ``{"cmd":"echo `date`"}`` |"#
+        );
+
+        let result = formatter
+            .with_option("style", "list")
+            .transform(info, None)
+            .unwrap();
+        assert_eq!(
+            result.message,
+            "- **error**: exit code 1 | retrying\n  second try\n\n  ```json\n  {\n    \"cmd\": \"echo `date`\"\n  }\n  ```"
+        );
+    }
+
+    #[test]
+    fn test_markdown_meta_escaping() {
+        let info = LogInfo::new("info", "copied")
+            .add_meta("path", "C:\\tmp")
+            .add_meta("pipe", "a|b");
+
+        let result = markdown().transform(info, None).unwrap();
+        assert_eq!(
+            result.message,
+            r#"| info | copied | `{"path":"C:\\tmp","pipe":"a\|b"}` |"#
+        );
+
+        let info = LogInfo::new("info", "quoted").add_meta("code", "```rust```");
+        let result = markdown()
+            .with_option("style", "list")
+            .transform(info, None)
+            .unwrap();
+        assert_eq!(
+            result.message,
+            "- **info**: quoted\n\n  ````json\n  {\n    \"code\": \"```rust```\"\n  }\n  ````"
+        );
+    }
+
+    #[test]
+    fn test_markdown_table() {
+        let records = vec![
+            LogInfo::new("info", "build started"),
+            LogInfo::new("warn", "flaky test").add_meta("test", json!("io::retry")),
+        ];
+
+        let table = markdown_table(&records, &markdown().with_option("style", "list"));
+        assert_eq!(
+            table,
+            "| Level | Message | Meta |\n\
+             | --- | --- | --- |\n\
+             | info | build started |  |\n\
+             | warn | flaky test | `{\"test\":\"io::retry\"}` |"
+        );
+    }
+}
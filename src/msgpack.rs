@@ -0,0 +1,78 @@
+use crate::utils::record::{decode_object, encode_object};
+use crate::{DecodeError, Format, FormatOptions, LogInfo};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::Value;
+
+/// Encodes a log info as a MessagePack map with the same shape as `json()`.
+pub fn encode_msgpack(info: &LogInfo) -> Vec<u8> {
+    rmp_serde::to_vec(&encode_object(info)).expect("encoding a JSON value cannot fail")
+}
+
+/// Decodes a MessagePack map produced by `encode_msgpack` back into a log info.
+pub fn decode_msgpack(bytes: &[u8]) -> Result<LogInfo, DecodeError> {
+    let value: Value =
+        rmp_serde::from_slice(bytes).map_err(|e| DecodeError::Invalid(e.to_string()))?;
+    decode_object(value)
+}
+
+/// Replaces the message with the base64 of the MessagePack encoding of the log info,
+/// since `message` can only hold text. Use `encode_msgpack` to get the raw bytes.
+pub fn msgpack() -> Format {
+    Format::new(|mut info: LogInfo, _opts: FormatOptions| {
+        info.message = STANDARD.encode(encode_msgpack(&info));
+        Some(info)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+    use serde_json::json;
+
+    #[test]
+    fn test_msgpack_round_trip() {
+        let info = LogInfo::new("info", "User logged in")
+            .add_meta("user_id", 12345)
+            .add_meta("ratio", 0.5)
+            .add_meta("negative", -7)
+            .add_meta("tags", json!(["a", null, true, {"nested": [1.5]}]));
+
+        let bytes = encode_msgpack(&info);
+        let decoded = decode_msgpack(&bytes).unwrap();
+
+        let expected = json().transform(info.clone(), None).unwrap().message;
+        let actual = json().transform(decoded, None).unwrap().message;
+        assert_eq!(actual, expected);
+
+        let formatted = msgpack().transform(info, None).unwrap();
+        assert_eq!(STANDARD.decode(formatted.message).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_msgpack_core_key_collisions() {
+        let info = LogInfo::new("info", "hi")
+            .add_meta("level", "meta level")
+            .add_meta("message", "meta message")
+            .add_meta("meta_level", "prefixed")
+            .add_meta("meta_data", 1);
+
+        let decoded = decode_msgpack(&encode_msgpack(&info)).unwrap();
+        assert_eq!(decoded.level, "info");
+        assert_eq!(decoded.message, "hi");
+        assert_eq!(decoded.meta, info.meta);
+    }
+
+    #[test]
+    fn test_msgpack_decode_errors() {
+        assert!(matches!(
+            decode_msgpack(&[0xc1]),
+            Err(DecodeError::Invalid(_))
+        ));
+        let not_a_record = rmp_serde::to_vec(&json!({"level": "info"})).unwrap();
+        assert_eq!(
+            decode_msgpack(&not_a_record).unwrap_err(),
+            DecodeError::MissingField("message")
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use crate::utils::levels::{plain_level, plain_severity};
+use crate::utils::time::{record_time_or_now, unix_nanos};
+use crate::{Format, FormatOptions, LogInfo};
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+struct OtlpOptions {
+    timestamp_key: String,
+    trace_id_key: String,
+    span_id_key: String,
+}
+
+impl OtlpOptions {
+    fn from(opts: &FormatOptions) -> Self {
+        let get = |key: &str, default: &str| {
+            opts.as_ref()
+                .and_then(|o| o.get(key))
+                .cloned()
+                .unwrap_or_else(|| default.to_string())
+        };
+        OtlpOptions {
+            timestamp_key: get("timestamp_key", "timestamp"),
+            trace_id_key: get("trace_id_key", "trace_id"),
+            span_id_key: get("span_id_key", "span_id"),
+        }
+    }
+}
+
+/// Maps a plain level to an OpenTelemetry `SeverityNumber`.
+fn severity_number(level: &str) -> u8 {
+    if matches!(level, "silly" | "trace") {
+        return 1; // TRACE
+    }
+    match plain_severity(level) {
+        Some(0..=2) => 21, // FATAL
+        Some(3) => 17,     // ERROR
+        Some(4) => 13,     // WARN
+        Some(5) => 10,     // INFO2
+        Some(6) => 9,      // INFO
+        Some(_) => 5,      // DEBUG
+        None => 0,         // UNSPECIFIED
+    }
+}
+
+/// Converts a JSON value into an OTLP `AnyValue`.
+fn any_value(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({}),
+        Value::Bool(b) => json!({ "boolValue": b }),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            // 64-bit integers are encoded as strings in OTLP/JSON
+            (Some(i), _) => json!({ "intValue": i.to_string() }),
+            // `intValue` is signed, so larger integers are kept exact as strings
+            (None, Some(u)) => json!({ "stringValue": u.to_string() }),
+            _ => json!({ "doubleValue": n.as_f64().unwrap_or_default() }),
+        },
+        Value::String(s) => json!({ "stringValue": s }),
+        Value::Array(arr) => {
+            json!({ "arrayValue": { "values": arr.iter().map(any_value).collect::<Vec<_>>() } })
+        }
+        Value::Object(map) => json!({ "kvlistValue": { "values": key_values(map.iter()) } }),
+    }
+}
+
+fn key_values<'a>(entries: impl Iterator<Item = (&'a String, &'a Value)>) -> Vec<Value> {
+    let mut entries: Vec<_> = entries.collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+        .into_iter()
+        .map(|(k, v)| json!({ "key": k, "value": any_value(v) }))
+        .collect()
+}
+
+/// Returns the lowercase hex id if the meta value is a valid id of `len` hex digits.
+fn hex_id(value: Option<&Value>, len: usize) -> Option<String> {
+    let id = value?.as_str()?;
+    if id.len() == len && id.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(id.to_lowercase())
+    } else {
+        None
+    }
+}
+
+fn log_record(info: &LogInfo, options: &OtlpOptions) -> Value {
+    let time = record_time_or_now(info, &options.timestamp_key);
+
+    let mut record = Map::new();
+    record.insert(
+        "timeUnixNano".to_string(),
+        Value::String(unix_nanos(&time).to_string()),
+    );
+    record.insert(
+        "observedTimeUnixNano".to_string(),
+        Value::String(unix_nanos(&Utc::now()).to_string()),
+    );
+    let level = plain_level(&info.level);
+    record.insert("severityNumber".to_string(), json!(severity_number(&level)));
+    record.insert("severityText".to_string(), json!(level));
+    record.insert("body".to_string(), json!({ "stringValue": info.message }));
+
+    let mut skipped = vec![options.timestamp_key.as_str()];
+
+    if let Some(trace_id) = hex_id(info.meta.get(&options.trace_id_key), 32) {
+        record.insert("traceId".to_string(), Value::String(trace_id));
+        skipped.push(&options.trace_id_key);
+    }
+    if let Some(span_id) = hex_id(info.meta.get(&options.span_id_key), 16) {
+        record.insert("spanId".to_string(), Value::String(span_id));
+        skipped.push(&options.span_id_key);
+    }
+
+    let attributes = key_values(
+        info.meta
+            .iter()
+            .filter(|(k, _)| !skipped.contains(&k.as_str())),
+    );
+    record.insert("attributes".to_string(), Value::Array(attributes));
+
+    Value::Object(record)
+}
+
+/// Renders the log info as an OTLP/JSON `LogRecord`.
+pub fn otlp_json() -> Format {
+    Format::new(|info: LogInfo, opts: FormatOptions| {
+        let record = log_record(&info, &OtlpOptions::from(&opts)).to_string();
+        Some(LogInfo {
+            level: info.level,
+            message: record,
+            meta: info.meta,
+        })
+    })
+}
+
+/// Wraps a batch of records into an OTLP/JSON `ExportLogsServiceRequest` body
+/// (`{"resourceLogs":[...]}`), ready to POST to a collector's `/v1/logs`.
+pub fn otlp_resource_logs(
+    records: &[LogInfo],
+    resource_attributes: &HashMap<String, Value>,
+    opts: FormatOptions,
+) -> String {
+    let options = OtlpOptions::from(&opts);
+    let log_records: Vec<Value> = records.iter().map(|r| log_record(r, &options)).collect();
+
+    json!({
+        "resourceLogs": [{
+            "resource": { "attributes": key_values(resource_attributes.iter()) },
+            "scopeLogs": [{
+                "scope": { "name": "logform", "version": env!("CARGO_PKG_VERSION") },
+                "logRecords": log_records,
+            }],
+        }]
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_otlp_json_formatter() {
+        let formatter = otlp_json();
+
+        let info = LogInfo::new("warn", "Disk almost full")
+            .add_meta("timestamp", "2024-08-27T02:39:15Z")
+            .add_meta("trace_id", "5B8EFFF798038103D269B633813FC60C")
+            .add_meta("span_id", "eee19b7ec3c1b174")
+            .add_meta("free_bytes", 1024)
+            .add_meta("ratio", 0.95)
+            .add_meta("inodes", u64::MAX)
+            .add_meta("tags", json!(["disk", true]));
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        let record: Value = serde_json::from_str(&result.message).unwrap();
+        assert_eq!(record["timeUnixNano"], "1724726355000000000");
+        assert_eq!(record["severityNumber"], 13);
+        assert_eq!(record["severityText"], "warn");
+        assert_eq!(record["body"]["stringValue"], "Disk almost full");
+        assert_eq!(record["traceId"], "5b8efff798038103d269b633813fc60c");
+        assert_eq!(record["spanId"], "eee19b7ec3c1b174");
+        assert_eq!(
+            record["attributes"],
+            json!([
+                {"key": "free_bytes", "value": {"intValue": "1024"}},
+                {"key": "inodes", "value": {"stringValue": "18446744073709551615"}},
+                {"key": "ratio", "value": {"doubleValue": 0.95}},
+                {"key": "tags", "value": {"arrayValue": {"values": [
+                    {"stringValue": "disk"}, {"boolValue": true}
+                ]}}},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_otlp_resource_logs() {
+        let records = vec![
+            LogInfo::new("info", "first"),
+            LogInfo::new("error", "second").add_meta("trace_id", "not-a-trace-id"),
+        ];
+        let mut resource = HashMap::new();
+        resource.insert("service.name".to_string(), json!("checkout"));
+
+        let payload: Value =
+            serde_json::from_str(&otlp_resource_logs(&records, &resource, None)).unwrap();
+
+        let resource_logs = &payload["resourceLogs"][0];
+        assert_eq!(
+            resource_logs["resource"]["attributes"][0],
+            json!({"key": "service.name", "value": {"stringValue": "checkout"}})
+        );
+
+        let log_records = resource_logs["scopeLogs"][0]["logRecords"]
+            .as_array()
+            .unwrap();
+        assert_eq!(log_records.len(), 2);
+        assert_eq!(log_records[1]["severityNumber"], 17);
+        // an invalid trace id is kept as a plain attribute
+        assert!(log_records[1].get("traceId").is_none());
+        assert_eq!(log_records[1]["attributes"][0]["key"], "trace_id");
+    }
+}
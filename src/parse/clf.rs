@@ -0,0 +1,156 @@
+use super::Scanner;
+use crate::{LogInfo, ParseError};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+/// Undoes the `\xHH` escapes `access_log()` writes inside quoted fields.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(i) = rest.find("\\x") {
+        unescaped.push_str(&rest[..i]);
+        let hex = rest.get(i + 2..i + 4);
+        match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(byte) if byte.is_ascii() => {
+                unescaped.push(byte as char);
+                rest = &rest[i + 4..];
+            }
+            _ => {
+                unescaped.push_str("\\x");
+                rest = &rest[i + 2..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Reads a `"..."` field; quotes inside are escaped, so the field ends at the next quote.
+fn quoted<'a>(scanner: &mut Scanner<'a>, what: &str) -> Result<&'a str, ParseError> {
+    scanner.expect("\"", &format!("`\"` starting the {}", what))?;
+    let value = scanner.take_until('"', what)?;
+    scanner.next_char();
+    Ok(value)
+}
+
+/// `-` marks a missing value.
+fn insert_text(info: &mut LogInfo, key: &str, value: &str) {
+    if value != "-" {
+        info.meta
+            .insert(key.to_string(), Value::String(unescape(value)));
+    }
+}
+
+fn insert_number(
+    scanner: &Scanner,
+    info: &mut LogInfo,
+    key: &str,
+    value: &str,
+) -> Result<(), ParseError> {
+    if value != "-" {
+        let number: u64 = value
+            .parse()
+            .map_err(|_| ParseError::new(scanner.pos - value.len(), format!("invalid {}", key)))?;
+        info.meta.insert(key.to_string(), Value::from(number));
+    }
+    Ok(())
+}
+
+/// Parses a Common or Combined Log Format line, as written by `access_log()`, into the
+/// request meta it reads (`remote_addr`, `remote_user`, `timestamp`, `method`, `path`,
+/// `protocol`, `status`, `bytes`, `referer`, `user_agent`). The request line becomes
+/// the message.
+pub fn clf(line: &str) -> Result<LogInfo, ParseError> {
+    let mut scanner = Scanner::new(line);
+    let mut info = LogInfo::new("info", "");
+
+    let remote_addr = scanner.word("remote address")?;
+    insert_text(&mut info, "remote_addr", remote_addr);
+    scanner.expect(" ", "a space")?;
+    scanner.word("identity")?;
+    scanner.expect(" ", "a space")?;
+    let remote_user = scanner.word("remote user")?;
+    insert_text(&mut info, "remote_user", remote_user);
+    scanner.expect(" [", "`[` starting the time")?;
+
+    let time_start = scanner.pos;
+    let time = scanner.take_until(']', "time")?;
+    let time = DateTime::parse_from_str(time, "%d/%b/%Y:%H:%M:%S %z")
+        .map_err(|_| ParseError::new(time_start, "invalid time"))?;
+    info.meta.insert(
+        "timestamp".to_string(),
+        Value::String(time.with_timezone(&Utc).to_rfc3339()),
+    );
+    scanner.expect("] ", "`]`")?;
+
+    let request_start = scanner.pos + 1;
+    let request = quoted(&mut scanner, "request")?;
+    if request != "-" {
+        let parts: Vec<&str> = request.split(' ').collect();
+        let [method, path, protocol] = parts[..] else {
+            return Err(ParseError::new(
+                request_start,
+                "expected `METHOD PATH PROTOCOL`",
+            ));
+        };
+        insert_text(&mut info, "method", method);
+        insert_text(&mut info, "path", path);
+        insert_text(&mut info, "protocol", protocol);
+        info.message = unescape(request);
+    }
+
+    scanner.expect(" ", "a space")?;
+    let status = scanner.word("status")?;
+    insert_number(&scanner, &mut info, "status", status)?;
+    scanner.expect(" ", "a space")?;
+    let bytes = scanner.word("size")?;
+    insert_number(&scanner, &mut info, "bytes", bytes)?;
+
+    // The Combined Log Format adds the referer and user agent
+    if !scanner.is_done() {
+        scanner.expect(" ", "a space")?;
+        let referer = quoted(&mut scanner, "referer")?;
+        insert_text(&mut info, "referer", referer);
+        scanner.expect(" ", "a space")?;
+        let user_agent = quoted(&mut scanner, "user agent")?;
+        insert_text(&mut info, "user_agent", user_agent);
+    }
+
+    if !scanner.is_done() {
+        return Err(scanner.error("unexpected text after the log line"));
+    }
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_clf() {
+        let info = clf(
+            r#"10.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] "GET /search?q=\x22rust\x22 HTTP/1.0" 200 2326 "-" "curl/8.0""#,
+        )
+        .unwrap();
+
+        assert_eq!(info.message, "GET /search?q=\"rust\" HTTP/1.0");
+        assert_eq!(info.meta["remote_addr"], json!("10.0.0.1"));
+        assert_eq!(info.meta["remote_user"], json!("frank"));
+        assert_eq!(info.meta["timestamp"], json!("2000-10-10T20:55:36+00:00"));
+        assert_eq!(info.meta["path"], json!("/search?q=\"rust\""));
+        assert_eq!(info.meta["status"], json!(200));
+        assert_eq!(info.meta["bytes"], json!(2326));
+        assert!(!info.meta.contains_key("referer"));
+        assert_eq!(info.meta["user_agent"], json!("curl/8.0"));
+
+        let error =
+            clf(r#"10.0.0.1 - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.0" 2xx 0"#).unwrap_err();
+        assert_eq!(
+            (error.offset, error.reason.as_str()),
+            (59, "invalid status")
+        );
+        let error = clf(r#"10.0.0.1 - - [yesterday] "GET / HTTP/1.0" 200 0"#).unwrap_err();
+        assert_eq!(error.offset, 14);
+    }
+}
@@ -0,0 +1,48 @@
+use crate::utils::record::from_object;
+use crate::{DecodeError, LogInfo, ParseError};
+use serde_json::Value;
+
+/// Parses a line written by `json()` with its default keys: `level` and `message`
+/// strings, with everything else becoming meta.
+pub fn json(line: &str) -> Result<LogInfo, ParseError> {
+    let value: Value = serde_json::from_str(line).map_err(|e| {
+        // serde_json reports 1-based lines and columns
+        let line_start: usize = line
+            .split_inclusive('\n')
+            .take(e.line().saturating_sub(1))
+            .map(str::len)
+            .sum();
+        let reason = e.to_string();
+        let reason = reason
+            .rfind(" at line ")
+            .map_or(reason.as_str(), |i| &reason[..i]);
+        ParseError::new(line_start + e.column().saturating_sub(1), reason)
+    })?;
+
+    from_object(value).map_err(|e| match e {
+        DecodeError::MissingField(field) => {
+            ParseError::new(0, format!("no string `{}` field", field))
+        }
+        other => ParseError::new(0, other.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_json() {
+        let info = json(r#"{"level":"info","message":"User logged in","user_id":12345}"#).unwrap();
+        assert_eq!(info.level, "info");
+        assert_eq!(info.message, "User logged in");
+        assert_eq!(info.meta["user_id"], json!(12345));
+
+        let error = json(r#"{"level":"info","message":}"#).unwrap_err();
+        assert_eq!(error.offset, 26);
+
+        let error = json(r#"{"level":"info"}"#).unwrap_err();
+        assert_eq!(error.reason, "no string `message` field");
+    }
+}
@@ -0,0 +1,124 @@
+use super::Scanner;
+use crate::{LogInfo, ParseError};
+use serde_json::Value;
+
+/// Reads a quoted value, undoing the escapes `logfmt()` writes.
+fn quoted(scanner: &mut Scanner) -> Result<String, ParseError> {
+    scanner.expect("\"", "`\"`")?;
+    let mut value = String::new();
+    loop {
+        let escape_start = scanner.pos;
+        match scanner.next_char() {
+            None => return Err(ParseError::new(scanner.pos, "unterminated quoted value")),
+            Some('"') => return Ok(value),
+            Some('\\') => match scanner.next_char() {
+                Some('n') => value.push('\n'),
+                Some('r') => value.push('\r'),
+                Some('t') => value.push('\t'),
+                Some(c @ ('"' | '\\')) => value.push(c),
+                _ => return Err(ParseError::new(escape_start, "invalid escape")),
+            },
+            Some(c) => value.push(c),
+        }
+    }
+}
+
+/// Quoted values are always strings. Unquoted values that read as JSON numbers,
+/// booleans, arrays or objects are taken as such and an empty one is null.
+fn typed(raw: String, was_quoted: bool) -> Value {
+    if was_quoted {
+        return Value::String(raw);
+    }
+    if raw.is_empty() {
+        return Value::Null;
+    }
+    match serde_json::from_str::<Value>(&raw) {
+        Ok(value) if !value.is_string() && !value.is_null() => value,
+        _ => Value::String(raw),
+    }
+}
+
+/// Parses a line of `key=value` pairs as written by `logfmt()`. `level` and `msg`
+/// become the level and message, every other pair becomes meta.
+pub fn logfmt(line: &str) -> Result<LogInfo, ParseError> {
+    let mut scanner = Scanner::new(line);
+    let mut level = None;
+    let mut message = None;
+    let mut info = LogInfo::new("", "");
+
+    scanner.skip_spaces();
+    while !scanner.is_done() {
+        let key_start = scanner.pos;
+        let key = scanner.take_until('=', "key, expected `=`")?;
+        if key.is_empty() || key.contains(' ') {
+            return Err(ParseError::new(key_start, "expected a key"));
+        }
+        scanner.expect("=", "`=`")?;
+
+        let (raw, was_quoted) = if scanner.peek() == Some('"') {
+            (quoted(&mut scanner)?, true)
+        } else {
+            let len = scanner.rest().find(' ').unwrap_or(scanner.rest().len());
+            let raw = scanner.rest()[..len].to_string();
+            scanner.pos += len;
+            (raw, false)
+        };
+
+        if !scanner.is_done() && scanner.peek() != Some(' ') {
+            return Err(scanner.error("expected a space between pairs"));
+        }
+        scanner.skip_spaces();
+
+        // The level and message are always text, even when they look like numbers
+        match key {
+            "level" => level = Some(raw),
+            "msg" => message = Some(raw),
+            _ => {
+                info.meta.insert(key.to_string(), typed(raw, was_quoted));
+            }
+        }
+    }
+
+    info.level = level.ok_or_else(|| ParseError::new(line.len(), "no `level` pair"))?;
+    info.message = message.unwrap_or_default();
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_logfmt() {
+        let info = logfmt(
+            r#"level=info msg="User logged in" agent="curl/8.0 \"beta\"" empty="" missing= path=/login ports=[80,443] tags="[\"a\",\"b\"]" user_id=12345"#,
+        )
+        .unwrap();
+
+        assert_eq!(info.level, "info");
+        assert_eq!(info.message, "User logged in");
+        assert_eq!(info.meta["agent"], json!("curl/8.0 \"beta\""));
+        assert_eq!(info.meta["empty"], json!(""));
+        assert_eq!(info.meta["missing"], json!(null));
+        assert_eq!(info.meta["path"], json!("/login"));
+        assert_eq!(info.meta["ports"], json!([80, 443]));
+        assert_eq!(info.meta["tags"], json!("[\"a\",\"b\"]"));
+        assert_eq!(info.meta["user_id"], json!(12345));
+
+        let info = logfmt(r#"level=info id="12345" flag="true" list="[1]" n=1.5"#).unwrap();
+        assert_eq!(info.meta["id"], json!("12345"));
+        assert_eq!(info.meta["flag"], json!("true"));
+        assert_eq!(info.meta["list"], json!("[1]"));
+        assert_eq!(info.meta["n"], json!(1.5));
+
+        let error = logfmt(r#"level=info msg="unterminated"#).unwrap_err();
+        assert_eq!(error.offset, 28);
+        let error = logfmt(r#"level=info msg="bad \x""#).unwrap_err();
+        assert_eq!(
+            (error.offset, error.reason.as_str()),
+            (20, "invalid escape")
+        );
+        assert_eq!(logfmt("msg=hi").unwrap_err().reason, "no `level` pair");
+    }
+}
@@ -0,0 +1,89 @@
+mod clf;
+mod json;
+mod logfmt;
+mod simple;
+mod syslog;
+
+pub use clf::clf;
+pub use json::json;
+pub use logfmt::logfmt;
+pub use simple::simple;
+pub use syslog::syslog;
+
+use crate::ParseError;
+
+/// Reads a line from left to right, keeping track of the position for errors.
+struct Scanner<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(input: &'a str) -> Self {
+        Scanner { input, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn is_done(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, reason: impl Into<String>) -> ParseError {
+        ParseError::new(self.pos, reason)
+    }
+
+    /// Consumes `expected` or fails, naming what was expected.
+    fn expect(&mut self, expected: &str, what: &str) -> Result<(), ParseError> {
+        if self.rest().starts_with(expected) {
+            self.pos += expected.len();
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {}", what)))
+        }
+    }
+
+    /// Takes everything up to the next `end`, leaving `end` unconsumed.
+    fn take_until(&mut self, end: char, what: &str) -> Result<&'a str, ParseError> {
+        match self.rest().find(end) {
+            Some(len) => {
+                let taken = &self.rest()[..len];
+                self.pos += len;
+                Ok(taken)
+            }
+            None => Err(ParseError::new(
+                self.input.len(),
+                format!("unterminated {}", what),
+            )),
+        }
+    }
+
+    /// Takes a non-empty run of characters up to the next space or the end of the line.
+    fn word(&mut self, what: &str) -> Result<&'a str, ParseError> {
+        let len = self.rest().find(' ').unwrap_or(self.rest().len());
+        if len == 0 {
+            return Err(self.error(format!("expected {}", what)));
+        }
+        let taken = &self.rest()[..len];
+        self.pos += len;
+        Ok(taken)
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{LogInfo, ParseError};
+use serde_json::{Map, Value};
+
+/// Finds the meta object `simple()` appends after the message: the shortest suffix,
+/// after a space, that is a JSON object. Returns its start and the object.
+fn trailing_meta(line: &str) -> Option<(usize, Map<String, Value>)> {
+    if !line.ends_with('}') {
+        return None;
+    }
+    line.rmatch_indices(" {").find_map(|(start, _)| {
+        match serde_json::from_str(&line[start + 1..]) {
+            Ok(Value::Object(meta)) => Some((start, meta)),
+            _ => None,
+        }
+    })
+}
+
+/// Parses a line written by `simple()`: `level: message`, followed by the meta as a
+/// JSON object when there is any. A message that itself ends with a JSON object can't
+/// be told apart from meta.
+pub fn simple(line: &str) -> Result<LogInfo, ParseError> {
+    let colon = line
+        .find(':')
+        .ok_or_else(|| ParseError::new(line.len(), "expected `:` after the level"))?;
+    let level = &line[..colon];
+    if level.is_empty() || level.contains(' ') {
+        return Err(ParseError::new(0, "expected a level before `:`"));
+    }
+
+    let rest = &line[colon + 1..];
+    let (body, meta) = match trailing_meta(rest) {
+        Some((start, meta)) => (&rest[..start], meta),
+        None => (rest, Map::new()),
+    };
+
+    // `simple()` puts a single space between the level and the message
+    let message = body
+        .strip_prefix(' ')
+        .ok_or_else(|| ParseError::new(colon + 1, "expected a space after `:`"))?;
+
+    let mut info = LogInfo::new(level, message);
+    info.meta.extend(meta);
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_simple() {
+        let info =
+            simple(r#"info: Payment {declined} {"amount":{"value":5},"user":"bob"}"#).unwrap();
+        assert_eq!(info.level, "info");
+        assert_eq!(info.message, "Payment {declined}");
+        assert_eq!(info.meta["amount"], json!({"value": 5}));
+        assert_eq!(info.meta["user"], json!("bob"));
+
+        let info = simple("warn: retrying: attempt 2").unwrap();
+        assert_eq!(info.message, "retrying: attempt 2");
+        assert!(info.meta.is_empty());
+
+        assert_eq!(simple("no level here").unwrap_err().offset, 13);
+        assert_eq!(simple("info:missing space").unwrap_err().offset, 5);
+    }
+}
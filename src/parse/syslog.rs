@@ -0,0 +1,221 @@
+use super::Scanner;
+use crate::{LogInfo, ParseError};
+use serde_json::{Map, Value};
+
+/// Level names for the syslog severities 0 to 7.
+const SEVERITY_LEVELS: [&str; 8] = [
+    "emerg", "alert", "crit", "error", "warning", "notice", "info", "debug",
+];
+
+/// Reads `<PRI>`, returning the facility and severity.
+fn priority(scanner: &mut Scanner) -> Result<(u8, u8), ParseError> {
+    scanner.expect("<", "`<` starting the priority")?;
+    let start = scanner.pos;
+    let digits = scanner.take_until('>', "priority")?;
+    let pri: u8 = digits
+        .parse()
+        .ok()
+        .filter(|pri| *pri <= 191 && !digits.is_empty() && digits.len() <= 3)
+        .ok_or_else(|| ParseError::new(start, "invalid priority"))?;
+    scanner.expect(">", "`>`")?;
+    Ok((pri >> 3, pri & 7))
+}
+
+/// A header field, where `-` means it is not set.
+fn field(scanner: &mut Scanner, what: &str) -> Result<Option<String>, ParseError> {
+    let value = scanner.word(what)?;
+    if !scanner.is_done() {
+        scanner.expect(" ", "a space")?;
+    }
+    Ok((value != "-").then(|| value.to_string()))
+}
+
+/// Reads RFC 5424 structured data: `-`, or `[id name="value" ...]` elements.
+fn structured_data(scanner: &mut Scanner) -> Result<Option<Map<String, Value>>, ParseError> {
+    if scanner.peek() == Some('-') {
+        scanner.next_char();
+        return Ok(None);
+    }
+
+    let mut elements = Map::new();
+    while scanner.peek() == Some('[') {
+        scanner.next_char();
+        let len = scanner
+            .rest()
+            .find([' ', ']'])
+            .ok_or_else(|| ParseError::new(scanner.input.len(), "unterminated structured data"))?;
+        if len == 0 {
+            return Err(scanner.error("expected a structured data id"));
+        }
+        let id = &scanner.rest()[..len];
+        scanner.pos += len;
+        let mut params = Map::new();
+
+        loop {
+            match scanner.next_char() {
+                Some(']') => break,
+                Some(' ') => {}
+                _ => {
+                    return Err(ParseError::new(
+                        scanner.pos.saturating_sub(1),
+                        "expected `]`",
+                    ))
+                }
+            }
+            let name = scanner.take_until('=', "param, expected `=`")?;
+            scanner.expect("=\"", "`=\"`")?;
+
+            let mut value = String::new();
+            loop {
+                match scanner.next_char() {
+                    None => return Err(scanner.error("unterminated param value")),
+                    Some('"') => break,
+                    Some('\\') => match scanner.peek() {
+                        Some(c @ ('"' | '\\' | ']')) => {
+                            scanner.next_char();
+                            value.push(c);
+                        }
+                        _ => value.push('\\'),
+                    },
+                    Some(c) => value.push(c),
+                }
+            }
+            params.insert(name.to_string(), Value::String(value));
+        }
+        elements.insert(id.to_string(), Value::Object(params));
+    }
+
+    if elements.is_empty() {
+        return Err(scanner.error("expected structured data"));
+    }
+    Ok(Some(elements))
+}
+
+fn rfc5424(scanner: &mut Scanner, info: &mut LogInfo) -> Result<(), ParseError> {
+    scanner.expect("1 ", "version 1")?;
+
+    for key in ["timestamp", "hostname", "app_name", "procid", "msgid"] {
+        if let Some(value) = field(scanner, key)? {
+            info.meta.insert(key.to_string(), Value::String(value));
+        }
+    }
+    if let Some(data) = structured_data(scanner)? {
+        info.meta
+            .insert("structured_data".to_string(), Value::Object(data));
+    }
+
+    if !scanner.is_done() {
+        scanner.expect(" ", "a space before the message")?;
+    }
+    info.message = scanner.rest().trim_start_matches('\u{feff}').to_string();
+    Ok(())
+}
+
+fn rfc3164(scanner: &mut Scanner, info: &mut LogInfo) -> Result<(), ParseError> {
+    // `Mmm dd hh:mm:ss`, with the day padded with a space
+    let timestamp = scanner
+        .rest()
+        .get(..15)
+        .filter(|t| t.as_bytes()[3] == b' ' && t.as_bytes()[6] == b' ' && t.as_bytes()[9] == b':')
+        .ok_or_else(|| scanner.error("expected a `Mmm dd hh:mm:ss` timestamp"))?;
+    info.meta.insert(
+        "timestamp".to_string(),
+        Value::String(timestamp.to_string()),
+    );
+    scanner.pos += 15;
+    scanner.expect(" ", "a space after the timestamp")?;
+
+    let hostname = scanner.word("hostname")?;
+    info.meta
+        .insert("hostname".to_string(), Value::String(hostname.to_string()));
+    scanner.expect(" ", "a space after the hostname")?;
+
+    // The tag is optional; a message without one starts right away
+    let rest = scanner.rest();
+    if let Some(colon) = rest.find(": ").filter(|i| !rest[..*i].contains(' ')) {
+        let tag = &rest[..colon];
+        let (app_name, procid) = match tag.strip_suffix(']').and_then(|t| t.split_once('[')) {
+            Some((app_name, procid)) => (app_name, Some(procid)),
+            None => (tag, None),
+        };
+        info.meta
+            .insert("app_name".to_string(), Value::String(app_name.to_string()));
+        if let Some(procid) = procid {
+            info.meta
+                .insert("procid".to_string(), Value::String(procid.to_string()));
+        }
+        scanner.pos += colon + 2;
+    }
+
+    info.message = scanner.rest().to_string();
+    Ok(())
+}
+
+/// Parses an RFC 5424 or RFC 3164 (BSD) syslog line. The severity becomes the level
+/// (`emerg` to `debug`); the facility and header fields become meta, and RFC 5424
+/// structured data is kept as an object of elements under `structured_data`.
+pub fn syslog(line: &str) -> Result<LogInfo, ParseError> {
+    let mut scanner = Scanner::new(line);
+    let (facility, severity) = priority(&mut scanner)?;
+
+    let mut info =
+        LogInfo::new(SEVERITY_LEVELS[severity as usize], "").add_meta("facility", facility);
+
+    if scanner.rest().starts_with("1 ") {
+        rfc5424(&mut scanner, &mut info)?;
+    } else {
+        rfc3164(&mut scanner, &mut info)?;
+    }
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_rfc5424() {
+        let info = syslog(
+            r#"<165>1 2003-10-11T22:14:15.003Z mymachine.example.com evntslog - ID47 [exampleSDID@32473 iut="3" eventSource="App\"lication\]"][meta][x@1 a="b"] An application event"#,
+        )
+        .unwrap();
+
+        assert_eq!(info.level, "notice");
+        assert_eq!(info.message, "An application event");
+        assert_eq!(info.meta["facility"], json!(20));
+        assert_eq!(info.meta["timestamp"], json!("2003-10-11T22:14:15.003Z"));
+        assert_eq!(info.meta["hostname"], json!("mymachine.example.com"));
+        assert_eq!(info.meta["app_name"], json!("evntslog"));
+        assert!(!info.meta.contains_key("procid"));
+        assert_eq!(info.meta["msgid"], json!("ID47"));
+        assert_eq!(
+            info.meta["structured_data"],
+            json!({"exampleSDID@32473": {"iut": "3", "eventSource": "App\"lication]"}, "meta": {}, "x@1": {"a": "b"}})
+        );
+
+        let info = syslog("<14>1 - - - - - -").unwrap();
+        assert_eq!((info.level.as_str(), info.message.as_str()), ("info", ""));
+    }
+
+    #[test]
+    fn test_parse_rfc3164() {
+        let info = syslog("<34>Oct  1 22:14:15 mymachine su[230]: 'su root' failed on /dev/pts/8")
+            .unwrap();
+
+        assert_eq!(info.level, "crit");
+        assert_eq!(info.message, "'su root' failed on /dev/pts/8");
+        assert_eq!(info.meta["facility"], json!(4));
+        assert_eq!(info.meta["timestamp"], json!("Oct  1 22:14:15"));
+        assert_eq!(info.meta["hostname"], json!("mymachine"));
+        assert_eq!(info.meta["app_name"], json!("su"));
+        assert_eq!(info.meta["procid"], json!("230"));
+
+        let error = syslog("<999>1 - - - - - -").unwrap_err();
+        assert_eq!(
+            (error.offset, error.reason.as_str()),
+            (1, "invalid priority")
+        );
+        assert_eq!(syslog("<13>Oct 1").unwrap_err().offset, 4);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::utils::host::hostname;
+use crate::utils::levels::bunyan_level;
+use crate::utils::time::record_time_or_now;
+use crate::{Format, FormatOptions, LogInfo};
+use serde_json::{json, Map, Value};
+
+/// Renders the log info as a pino record, readable by `pino-pretty`.
+pub fn pino() -> Format {
+    Format::new(|info: LogInfo, opts: FormatOptions| {
+        let opt = |key: &str| opts.as_ref().and_then(|o| o.get(key)).cloned();
+
+        let mut record = Map::new();
+
+        // Meta is flattened into the record, the core fields win on collisions
+        for (key, value) in &info.meta {
+            record.insert(key.clone(), value.clone());
+        }
+        record.remove("timestamp");
+        record.remove("splat");
+
+        let time = record_time_or_now(&info, "timestamp");
+
+        record.insert("level".to_string(), json!(bunyan_level(&info.level)));
+        record.insert("time".to_string(), json!(time.timestamp_millis()));
+        record.insert("pid".to_string(), json!(std::process::id()));
+        record.insert(
+            "hostname".to_string(),
+            json!(opt("hostname").unwrap_or_else(|| hostname().to_string())),
+        );
+        // pino only adds `name` when the logger was given one
+        if let Some(name) = opt("name") {
+            record.insert("name".to_string(), json!(name));
+        }
+        record.insert("msg".to_string(), json!(info.message));
+
+        Some(LogInfo {
+            level: info.level,
+            message: Value::Object(record).to_string(),
+            meta: info.meta,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pino_formatter() {
+        let formatter = pino();
+
+        let info = LogInfo::new("error", "Payment failed")
+            .add_meta("timestamp", "2024-08-27T02:39:15.250Z")
+            .add_meta("order_id", "A-1");
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        let record: Value = serde_json::from_str(&result.message).unwrap();
+        assert_eq!(record["level"], 50);
+        assert_eq!(record["time"], 1724726355250i64);
+        assert_eq!(record["pid"], std::process::id());
+        assert_eq!(record["hostname"], hostname());
+        assert_eq!(record["msg"], "Payment failed");
+        assert_eq!(record["order_id"], "A-1");
+        assert!(record.get("name").is_none());
+        assert!(record.get("v").is_none());
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{utils::format_json::format_json, Format, FormatOptions, LogInfo};
+use crate::utils::{format_json::format_json, safe_json::SafeLimits};
+use crate::{Format, FormatOptions, LogInfo};
 use serde_json::Value;
 
 pub fn pretty_print() -> Format {
@@ -15,7 +16,7 @@ pub fn pretty_print() -> Format {
         let colorize = opts
             .as_ref()
             .and_then(|o| o.get("colorize"))
-            .map_or(false, |v| v == "true");
+            .is_some_and(|v| v == "true");
 
         // Create a new JSON object with level and message
         let mut json_output = serde_json::Map::new();
@@ -27,6 +28,13 @@ pub fn pretty_print() -> Format {
             json_output.insert(key, value);
         }
 
+        // Apply the safe mode limits, if any
+        if let Some(limits) = SafeLimits::from(&opts) {
+            json_output = limits.apply(json_output, "level", "message", |o| {
+                format_json(&Value::Object(o.clone()), colorize)
+            });
+        }
+
         // Convert the JSON object to a Value
         let json_value = Value::Object(json_output);
 
@@ -63,4 +71,19 @@ mod tests {
         let result = formatter.transform(info, None).unwrap();
         println!("{}", result.message);
     }
+
+    #[test]
+    fn test_pretty_print_safe_mode() {
+        let formatter = pretty_print()
+            .with_option("max_array_length", "2")
+            .with_option("max_string_length", "5");
+
+        let info = LogInfo::new("info", "User logged in").add_meta("ids", json!([1, 2, 3]));
+
+        let result = formatter.transform(info, None).unwrap();
+        assert_eq!(
+            result.message,
+            "{\n  ids: [\n    1,\n    2,\n    '[Truncated: 1 more items]'\n  ],\n  level: 'info',\n  message: 'User ...[Truncated: 9 more chars]'\n}"
+        );
+    }
 }
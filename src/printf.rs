@@ -30,7 +30,7 @@ where
 {
     let printf_formatter = Printf::new(Arc::new(template_fn));
     Format::new(move |info: LogInfo, options: FormatOptions| {
-        printf_formatter.transform(info, options.map(|o| o.clone()))
+        printf_formatter.transform(info, options)
     })
 }
 
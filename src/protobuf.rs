@@ -0,0 +1,340 @@
+use crate::utils::time::record_time;
+use crate::{DecodeError, Format, FormatOptions, LogInfo};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use prost::Message;
+use prost_types::value::Kind;
+use prost_types::{ListValue, Struct, Timestamp};
+use serde_json::{Map, Number, Value};
+
+/// The `.proto` schema of the encoding, as published in `proto/logform.proto`.
+pub const PROTOBUF_SCHEMA: &str = include_str!("../proto/logform.proto");
+
+/// The messages of `proto/logform.proto`, named and laid out as prost-build would
+/// generate them for the `logform` package.
+mod proto {
+    use prost_types::{Struct, Timestamp};
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct LogInfo {
+        #[prost(string, tag = "1")]
+        pub level: String,
+        #[prost(string, tag = "2")]
+        pub message: String,
+        #[prost(message, optional, tag = "3")]
+        pub timestamp: Option<Timestamp>,
+        #[prost(message, optional, tag = "4")]
+        pub meta: Option<Struct>,
+        #[prost(message, repeated, tag = "5")]
+        pub integers: Vec<ExactInteger>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ExactInteger {
+        #[prost(string, tag = "1")]
+        pub pointer: String,
+        #[prost(oneof = "exact_integer::Value", tags = "2, 3")]
+        pub value: Option<exact_integer::Value>,
+    }
+
+    pub mod exact_integer {
+        #[derive(Clone, PartialEq, prost::Oneof)]
+        pub enum Value {
+            #[prost(sint64, tag = "2")]
+            Signed(i64),
+            #[prost(uint64, tag = "3")]
+            Unsigned(u64),
+        }
+    }
+}
+
+use proto::exact_integer::Value as Integer;
+use proto::ExactInteger;
+
+/// Escapes a key for use as a JSON pointer segment.
+fn pointer_segment(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Converts a JSON value, noting the exact value and location of every integer.
+fn to_proto_value(
+    value: &Value,
+    pointer: &str,
+    integers: &mut Vec<ExactInteger>,
+) -> prost_types::Value {
+    let kind = match value {
+        Value::Null => Kind::NullValue(0),
+        Value::Bool(b) => Kind::BoolValue(*b),
+        Value::Number(n) => {
+            let exact = match (n.as_i64(), n.as_u64()) {
+                (Some(i), _) => Some(Integer::Signed(i)),
+                (None, Some(u)) => Some(Integer::Unsigned(u)),
+                _ => None,
+            };
+            if exact.is_some() {
+                integers.push(ExactInteger {
+                    pointer: pointer.to_string(),
+                    value: exact,
+                });
+            }
+            Kind::NumberValue(n.as_f64().unwrap_or_default())
+        }
+        Value::String(s) => Kind::StringValue(s.clone()),
+        Value::Array(items) => Kind::ListValue(ListValue {
+            values: items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| to_proto_value(item, &format!("{}/{}", pointer, i), integers))
+                .collect(),
+        }),
+        Value::Object(map) => Kind::StructValue(to_proto_struct(map, pointer, integers)),
+    };
+    prost_types::Value { kind: Some(kind) }
+}
+
+fn to_proto_struct(
+    map: &Map<String, Value>,
+    pointer: &str,
+    integers: &mut Vec<ExactInteger>,
+) -> Struct {
+    Struct {
+        fields: map
+            .iter()
+            .map(|(key, value)| {
+                let pointer = format!("{}/{}", pointer, pointer_segment(key));
+                (key.clone(), to_proto_value(value, &pointer, integers))
+            })
+            .collect(),
+    }
+}
+
+fn from_proto_value(value: prost_types::Value) -> Value {
+    match value.kind {
+        None | Some(Kind::NullValue(_)) => Value::Null,
+        Some(Kind::BoolValue(b)) => Value::Bool(b),
+        Some(Kind::NumberValue(n)) => Number::from_f64(n).map_or(Value::Null, Value::Number),
+        Some(Kind::StringValue(s)) => Value::String(s),
+        Some(Kind::ListValue(list)) => {
+            Value::Array(list.values.into_iter().map(from_proto_value).collect())
+        }
+        Some(Kind::StructValue(s)) => Value::Object(from_proto_struct(s)),
+    }
+}
+
+fn from_proto_struct(s: Struct) -> Map<String, Value> {
+    s.fields
+        .into_iter()
+        .map(|(key, value)| (key, from_proto_value(value)))
+        .collect()
+}
+
+/// Encodes a log info as a `logform.LogInfo` protobuf message.
+pub fn encode_protobuf(info: &LogInfo) -> Vec<u8> {
+    let meta: Map<String, Value> = info
+        .meta
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+
+    let mut integers = Vec::new();
+    let meta = to_proto_struct(&meta, "", &mut integers);
+
+    let timestamp = record_time(info, "timestamp").map(|time| Timestamp {
+        seconds: time.timestamp(),
+        nanos: time.timestamp_subsec_nanos() as i32,
+    });
+
+    proto::LogInfo {
+        level: info.level.clone(),
+        message: info.message.clone(),
+        timestamp,
+        meta: Some(meta),
+        integers,
+    }
+    .encode_to_vec()
+}
+
+/// Decodes a message produced by `encode_protobuf` back into a log info, restoring
+/// integers from their exact values.
+pub fn decode_protobuf(bytes: &[u8]) -> Result<LogInfo, DecodeError> {
+    let record = proto::LogInfo::decode(bytes).map_err(|e| DecodeError::Invalid(e.to_string()))?;
+
+    let mut meta = Value::Object(record.meta.map(from_proto_struct).unwrap_or_default());
+    for integer in record.integers {
+        let exact = match integer.value {
+            Some(Integer::Signed(i)) => Value::from(i),
+            Some(Integer::Unsigned(u)) => Value::from(u),
+            None => continue,
+        };
+        if let Some(slot) = meta.pointer_mut(&integer.pointer) {
+            if slot.is_number() {
+                *slot = exact;
+            }
+        }
+    }
+
+    let mut info = LogInfo::new(record.level, record.message);
+    if let Value::Object(meta) = meta {
+        info.meta.extend(meta);
+    }
+    Ok(info)
+}
+
+/// Replaces the message with the base64 of the protobuf encoding of the log info,
+/// since `message` can only hold text. Use `encode_protobuf` to get the raw bytes.
+pub fn protobuf() -> Format {
+    Format::new(|mut info: LogInfo, _opts: FormatOptions| {
+        info.message = STANDARD.encode(encode_protobuf(&info));
+        Some(info)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_protobuf_round_trip() {
+        let info = LogInfo::new("warn", "Disk almost full")
+            .add_meta("timestamp", "2024-08-27T02:39:15.250Z")
+            .add_meta("free_bytes", u64::MAX)
+            .add_meta("offset", i64::MIN)
+            .add_meta("whole_float", 2.0)
+            .add_meta("ratio", 0.95)
+            .add_meta("message", "shadowed")
+            .add_meta(
+                "mounts",
+                json!({"/": {"ro": false, "inodes": [1, 2.5, null]}, "a/b~c": 7}),
+            );
+
+        let bytes = encode_protobuf(&info);
+        let decoded = decode_protobuf(&bytes).unwrap();
+
+        assert_eq!(decoded.level, info.level);
+        assert_eq!(decoded.message, info.message);
+        assert_eq!(decoded.meta, info.meta);
+        assert!(decoded.meta["whole_float"].is_f64());
+        assert!(decoded.meta["mounts"]["/"]["inodes"][0].is_u64());
+
+        let record = proto::LogInfo::decode(bytes.as_slice()).unwrap();
+        assert_eq!(
+            record.timestamp,
+            Some(Timestamp {
+                seconds: 1724726355,
+                nanos: 250_000_000
+            })
+        );
+
+        let formatted = protobuf().transform(info, None).unwrap();
+        assert_eq!(STANDARD.decode(formatted.message).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_protobuf_decode_errors() {
+        assert!(matches!(
+            decode_protobuf(&[0x0a, 0x05, b'a']),
+            Err(DecodeError::Invalid(_))
+        ));
+    }
+
+    /// Field numbers and types of each message in the published schema, with the
+    /// wire type they encode to.
+    fn schema_fields(message: &str) -> Vec<(u32, u8)> {
+        let start = PROTOBUF_SCHEMA
+            .find(&format!("message {} {{", message))
+            .unwrap_or_else(|| panic!("no message {} in the schema", message));
+        let body = &PROTOBUF_SCHEMA[start..];
+        let field = regex::Regex::new(r"^\s*(?:repeated\s+)?([\w.]+)\s+\w+\s*=\s*(\d+);").unwrap();
+
+        let mut fields = Vec::new();
+        let mut depth = 0;
+        for line in body.lines() {
+            depth += line.matches('{').count();
+            depth -= line.matches('}').count();
+            if let Some(captures) = field.captures(line) {
+                let wire_type = match &captures[1] {
+                    "int32" | "int64" | "uint32" | "uint64" | "sint32" | "sint64" | "bool" => 0,
+                    _ => 2,
+                };
+                fields.push((captures[2].parse().unwrap(), wire_type));
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+        fields.sort();
+        fields
+    }
+
+    fn read_varint(bytes: &mut &[u8]) -> u64 {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let (byte, rest) = bytes.split_first().unwrap();
+            *bytes = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        value
+    }
+
+    /// The field numbers and wire types found in an encoded message, with the
+    /// contents of the length-delimited fields.
+    fn wire_fields(mut bytes: &[u8]) -> Vec<(u32, u8, Vec<u8>)> {
+        let mut fields = Vec::new();
+        while !bytes.is_empty() {
+            let key = read_varint(&mut bytes);
+            let (number, wire_type) = ((key >> 3) as u32, (key & 7) as u8);
+            let contents = match wire_type {
+                0 => {
+                    read_varint(&mut bytes);
+                    Vec::new()
+                }
+                2 => {
+                    let len = read_varint(&mut bytes) as usize;
+                    let (contents, rest) = bytes.split_at(len);
+                    bytes = rest;
+                    contents.to_vec()
+                }
+                other => panic!("unexpected wire type {}", other),
+            };
+            fields.push((number, wire_type, contents));
+        }
+        fields
+    }
+
+    #[test]
+    fn test_protobuf_matches_schema() {
+        let info = LogInfo::new("info", "hi")
+            .add_meta("timestamp", "2024-08-27T02:39:15Z")
+            .add_meta("signed", -1)
+            .add_meta("unsigned", u64::MAX);
+        let bytes = encode_protobuf(&info);
+
+        let fields = wire_fields(&bytes);
+        let mut seen: Vec<(u32, u8)> = fields.iter().map(|(n, w, _)| (*n, *w)).collect();
+        seen.dedup();
+        assert_eq!(seen, schema_fields("LogInfo"));
+
+        let mut seen: Vec<(u32, u8)> = fields
+            .iter()
+            .filter(|(number, _, _)| *number == 5)
+            .flat_map(|(_, _, contents)| wire_fields(contents))
+            .map(|(n, w, _)| (n, w))
+            .collect();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen, schema_fields("ExactInteger"));
+
+        // `signed` is a zigzag-encoded sint64: -1 is written as 1
+        let integers = proto::LogInfo::decode(bytes.as_slice()).unwrap().integers;
+        assert_eq!(integers[0].value, Some(Integer::Signed(-1)));
+        let signed = fields
+            .iter()
+            .filter(|(number, _, _)| *number == 5)
+            .map(|(_, _, contents)| contents)
+            .find(|contents| contents.ends_with(&[0x10, 0x01]));
+        assert!(signed.is_some());
+    }
+}
@@ -0,0 +1,64 @@
+use crate::utils::levels::syslog_severity;
+use crate::{Format, FormatOptions, LogInfo};
+
+pub fn sd_prefix() -> Format {
+    Format::new(|mut info: LogInfo, _opts: FormatOptions| {
+        // Unknown levels are logged at the journal's default, info
+        let prefix = format!("<{}>", syslog_severity(&info.level).unwrap_or(6));
+
+        // Every line gets the prefix, otherwise journald logs continuation lines at
+        // the default priority. A trailing newline doesn't start a new line.
+        let (body, trailing_newline) = match info.message.strip_suffix('\n') {
+            Some(body) => (body, true),
+            None => (info.message.as_str(), false),
+        };
+
+        let mut message = body
+            .split('\n')
+            .map(|line| format!("{}{}", prefix, line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if trailing_newline {
+            message.push('\n');
+        }
+
+        info.message = message;
+        Some(info)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{combine, simple};
+
+    #[test]
+    fn test_sd_prefix_format() {
+        let formatter = combine(vec![simple(), sd_prefix()]);
+
+        let info = LogInfo::new("error", "Payment failed\n  at checkout.rs:42");
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        assert_eq!(
+            result.message,
+            "<3>error: Payment failed\n<3>  at checkout.rs:42"
+        );
+    }
+
+    #[test]
+    fn test_sd_prefix_levels() {
+        let formatter = sd_prefix();
+
+        let result = formatter
+            .transform(LogInfo::new("warn", "careful\n"), None)
+            .unwrap();
+        assert_eq!(result.message, "<4>careful\n");
+
+        let result = formatter
+            .transform(LogInfo::new("custom", "a\n\nb"), None)
+            .unwrap();
+        assert_eq!(result.message, "<6>a\n<6>\n<6>b");
+    }
+}
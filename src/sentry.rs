@@ -0,0 +1,391 @@
+use crate::utils::fields::value_to_text;
+use crate::utils::host::hostname;
+use crate::utils::levels::syslog_severity;
+use crate::utils::time::record_time_or_now;
+use crate::{Format, FormatOptions, LogInfo};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Meta keys with a dedicated place in the event, kept out of `extra`.
+const RESERVED_KEYS: [&str; 5] = ["splat", "error", "tags", "timestamp", "event_id"];
+
+/// The number of breadcrumbs Sentry SDKs keep by default.
+const DEFAULT_MAX_BREADCRUMBS: usize = 100;
+
+static EVENT_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Maps a syslog severity to one of Sentry's levels (`fatal`, `error`, `warning`,
+/// `info`, `debug`).
+fn sentry_level(severity: Option<u8>) -> &'static str {
+    match severity {
+        Some(0..=2) => "fatal",
+        Some(3) => "error",
+        Some(4) => "warning",
+        Some(7) => "debug",
+        _ => "info",
+    }
+}
+
+/// A 32 character hex id, taken from the `event_id` meta when it is one.
+fn event_id(info: &LogInfo) -> String {
+    if let Some(Value::String(id)) = info.meta.get("event_id") {
+        let id = id.replace('-', "").to_lowercase();
+        if id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return id;
+        }
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(info.level.as_bytes());
+    hasher.update(info.message.as_bytes());
+    hasher.update(
+        chrono::Utc::now()
+            .timestamp_nanos_opt()
+            .unwrap_or(0)
+            .to_be_bytes(),
+    );
+    hasher.update(EVENT_COUNTER.fetch_add(1, Ordering::Relaxed).to_be_bytes());
+    hasher.finalize()[..16]
+        .iter()
+        .fold(String::with_capacity(32), |mut hex, b| {
+            let _ = write!(hex, "{:02x}", b);
+            hex
+        })
+}
+
+/// Builds one exception value from an error in the chain.
+fn exception(error: &Value) -> Value {
+    match error {
+        Value::Object(map) => {
+            let text = |keys: &[&str]| keys.iter().find_map(|k| map.get(*k)).map(value_to_text);
+            let mut exception = json!({
+                "type": text(&["type", "kind", "name"]).unwrap_or_else(|| "Error".to_string()),
+                "value": text(&["value", "message"]).unwrap_or_default(),
+            });
+            if let Some(module) = text(&["module"]) {
+                exception["module"] = Value::String(module);
+            }
+            if let Some(stacktrace) = map.get("stacktrace") {
+                exception["stacktrace"] = stacktrace.clone();
+            }
+            exception
+        }
+        other => json!({ "type": "Error", "value": value_to_text(other) }),
+    }
+}
+
+/// Reads the error chain from the `error` meta, outermost error first. It is either
+/// a list of errors, or an error whose cause is nested under `source` or `cause`.
+fn error_values(error: &Value) -> Vec<Value> {
+    let mut chain = Vec::new();
+    match error {
+        Value::Array(errors) => chain.extend(errors.iter().map(exception)),
+        _ => {
+            let mut current = Some(error);
+            while let Some(error) = current {
+                chain.push(exception(error));
+                current = error.get("source").or_else(|| error.get("cause"));
+            }
+        }
+    }
+    // Sentry expects the root cause first
+    chain.reverse();
+    chain
+}
+
+/// Converts an error and its `source()` chain into the list form read from the
+/// `error` meta. Only the outermost error's type name is known.
+pub fn error_chain<E: std::error::Error + 'static>(error: &E) -> Value {
+    // `app::Wrap<std::io::Error>` is reported as `Wrap`
+    let type_name = std::any::type_name::<E>();
+    let path = type_name.split('<').next().unwrap_or(type_name);
+    let short_name = path.rsplit("::").next().unwrap_or(path);
+
+    let mut chain = vec![json!({ "type": short_name, "value": error.to_string() })];
+    let mut source = error.source();
+    while let Some(error) = source {
+        chain.push(json!({ "type": "Error", "value": error.to_string() }));
+        source = error.source();
+    }
+    Value::Array(chain)
+}
+
+fn breadcrumb(info: &LogInfo, severity: Option<u8>) -> Value {
+    let data: Map<String, Value> = info
+        .meta
+        .iter()
+        .filter(|(k, _)| !RESERVED_KEYS.contains(&k.as_str()) && k.as_str() != "category")
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+
+    let mut crumb = json!({
+        "timestamp": record_time_or_now(info, "timestamp").timestamp_millis() as f64 / 1000.0,
+        "type": "default",
+        "category": info.meta.get("category").map_or("log".to_string(), value_to_text),
+        "level": sentry_level(severity),
+        "message": info.message,
+    });
+    if !data.is_empty() {
+        crumb["data"] = Value::Object(data);
+    }
+    crumb
+}
+
+fn event(
+    info: &LogInfo,
+    severity: Option<u8>,
+    opts: &FormatOptions,
+    breadcrumbs: Vec<Value>,
+) -> Value {
+    let get = |key: &str| opts.as_ref().and_then(|o| o.get(key));
+
+    let tag_keys: Vec<&str> = get("tags")
+        .map(|keys| {
+            keys.split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut tags = Map::new();
+    if let Some(Value::Object(meta_tags)) = info.meta.get("tags") {
+        for (key, value) in meta_tags {
+            tags.insert(key.clone(), Value::String(value_to_text(value)));
+        }
+    }
+    for key in &tag_keys {
+        if let Some(value) = info.meta.get(*key).filter(|v| !v.is_null()) {
+            tags.insert(key.to_string(), Value::String(value_to_text(value)));
+        }
+    }
+
+    let extra: Map<String, Value> = info
+        .meta
+        .iter()
+        .filter(|(k, _)| !RESERVED_KEYS.contains(&k.as_str()) && !tag_keys.contains(&k.as_str()))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+
+    let mut logentry = json!({ "message": info.message });
+    if let Some(Value::Array(params)) = info.meta.get("splat") {
+        logentry["params"] = Value::Array(params.clone());
+    }
+
+    let mut event = json!({
+        "event_id": event_id(info),
+        "timestamp": record_time_or_now(info, "timestamp").to_rfc3339(),
+        "platform": "other",
+        "level": sentry_level(severity),
+        "logger": get("logger").map_or("logform", String::as_str),
+        "server_name": get("server_name").map_or(hostname(), String::as_str),
+        "logentry": logentry,
+    });
+
+    for key in ["release", "environment"] {
+        if let Some(value) = get(key) {
+            event[key] = Value::String(value.clone());
+        }
+    }
+    if let Some(error) = info.meta.get("error").filter(|e| !e.is_null()) {
+        event["exception"] = json!({ "values": error_values(error) });
+    }
+    if !tags.is_empty() {
+        event["tags"] = Value::Object(tags);
+    }
+    if !extra.is_empty() {
+        event["extra"] = Value::Object(extra);
+    }
+    if !breadcrumbs.is_empty() {
+        event["breadcrumbs"] = json!({ "values": breadcrumbs });
+    }
+    event
+}
+
+/// Turns records at or above `event_level` (default `error`) into Sentry event
+/// payloads. Less severe records are filtered out and kept in a ring of recent
+/// records instead, which is attached to the next event as its breadcrumbs.
+pub fn sentry_event() -> Format {
+    let recent: Arc<Mutex<VecDeque<Value>>> = Arc::new(Mutex::new(VecDeque::new()));
+
+    Format::new(move |mut info: LogInfo, opts: FormatOptions| {
+        let get = |key: &str| opts.as_ref().and_then(|o| o.get(key));
+
+        let threshold = get("event_level")
+            .and_then(|level| syslog_severity(level))
+            .unwrap_or(3);
+        let max_breadcrumbs = get("max_breadcrumbs")
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(DEFAULT_MAX_BREADCRUMBS);
+
+        let mut recent = recent.lock().unwrap_or_else(|e| e.into_inner());
+
+        let severity = syslog_severity(&info.level);
+
+        // Unknown levels are treated as info
+        if severity.unwrap_or(6) > threshold {
+            recent.push_back(breadcrumb(&info, severity));
+            while recent.len() > max_breadcrumbs {
+                recent.pop_front();
+            }
+            return None;
+        }
+
+        // Like in Sentry SDKs, the breadcrumbs stay around for later events
+        let breadcrumbs: Vec<Value> = recent.iter().cloned().collect();
+        let event = event(&info, severity, &opts, breadcrumbs);
+
+        info.message = serde_json::to_string(&event).unwrap_or_default();
+        Some(info)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt;
+
+    #[derive(Debug)]
+    struct ConnectError(std::io::Error);
+
+    impl fmt::Display for ConnectError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "could not connect to database")
+        }
+    }
+
+    impl std::error::Error for ConnectError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn test_sentry_event() {
+        let formatter = sentry_event()
+            .with_option("environment", "production")
+            .with_option("server_name", "web-1")
+            .with_option("tags", "region");
+
+        let breadcrumb = LogInfo::new("info", "GET /checkout")
+            .add_meta("timestamp", "2024-08-27T02:39:14.500Z")
+            .add_meta("category", "http")
+            .add_meta("status", 200);
+        assert!(formatter.transform(breadcrumb, None).is_none());
+
+        let error = ConnectError(std::io::Error::new(
+            std::io::ErrorKind::ConnectionRefused,
+            "connection refused",
+        ));
+        let info = LogInfo::new("error", "Checkout failed for %s")
+            .add_meta("timestamp", "2024-08-27T02:39:15Z")
+            .add_meta("event_id", "fc6d8c0c-43fc-4630-ad85-0c5c0c5a3f7e")
+            .add_meta("splat", json!(["bob"]))
+            .add_meta("error", error_chain(&error))
+            .add_meta("region", "eu-west-1")
+            .add_meta("tags", json!({"feature": "checkout"}))
+            .add_meta("cart_id", 42);
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        let event: Value = serde_json::from_str(&result.message).unwrap();
+        assert_eq!(
+            event,
+            json!({
+                "event_id": "fc6d8c0c43fc4630ad850c5c0c5a3f7e",
+                "timestamp": "2024-08-27T02:39:15+00:00",
+                "platform": "other",
+                "level": "error",
+                "logger": "logform",
+                "server_name": "web-1",
+                "environment": "production",
+                "logentry": {"message": "Checkout failed for %s", "params": ["bob"]},
+                "exception": {"values": [
+                    {"type": "Error", "value": "connection refused"},
+                    {"type": "ConnectError", "value": "could not connect to database"}
+                ]},
+                "tags": {"feature": "checkout", "region": "eu-west-1"},
+                "extra": {"cart_id": 42},
+                "breadcrumbs": {"values": [{
+                    "timestamp": 1724726354.5,
+                    "type": "default",
+                    "category": "http",
+                    "level": "info",
+                    "message": "GET /checkout",
+                    "data": {"status": 200}
+                }]}
+            })
+        );
+    }
+
+    #[derive(Debug)]
+    struct Wrap<E>(E);
+
+    impl<E: fmt::Display> fmt::Display for Wrap<E> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "wrapped: {}", self.0)
+        }
+    }
+
+    impl<E: fmt::Debug + fmt::Display> std::error::Error for Wrap<E> {}
+
+    #[test]
+    fn test_error_chain_generic_type() {
+        let error = Wrap(ConnectError(std::io::Error::other("refused")));
+        assert_eq!(
+            error_chain(&error),
+            json!([{"type": "Wrap", "value": "wrapped: could not connect to database"}])
+        );
+    }
+
+    #[test]
+    fn test_sentry_breadcrumb_ring() {
+        let formatter = sentry_event()
+            .with_option("max_breadcrumbs", "2")
+            .with_option("event_level", "warn");
+
+        for step in ["one", "two", "three"] {
+            assert!(formatter
+                .transform(LogInfo::new("debug", step), None)
+                .is_none());
+        }
+
+        let nested = json!({"type": "Timeout", "message": "request timed out",
+                            "cause": {"kind": "Io", "value": "reset by peer"}});
+        let info = LogInfo::new("warn", "retrying").add_meta("error", nested);
+        let event: Value =
+            serde_json::from_str(&formatter.transform(info, None).unwrap().message).unwrap();
+
+        assert_eq!(event["level"], "warning");
+        assert_eq!(event["event_id"].as_str().unwrap().len(), 32);
+        let messages: Vec<&str> = event["breadcrumbs"]["values"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| b["message"].as_str().unwrap())
+            .collect();
+        assert_eq!(messages, vec!["two", "three"]);
+        assert_eq!(
+            event["exception"]["values"],
+            json!([
+                {"type": "Io", "value": "reset by peer"},
+                {"type": "Timeout", "value": "request timed out"}
+            ])
+        );
+
+        // The breadcrumbs are kept for later events
+        let event: Value = serde_json::from_str(
+            &formatter
+                .transform(LogInfo::new("error", "again"), None)
+                .unwrap()
+                .message,
+        )
+        .unwrap();
+        assert_eq!(event["breadcrumbs"]["values"].as_array().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::utils::fields::value_to_text;
+use crate::utils::levels::syslog_severity;
+use crate::utils::time::record_time;
+use crate::{Format, FormatOptions, LogInfo};
+use std::collections::HashMap;
+
+/// Header and extension settings shared by `cef()` and `leef()`.
+struct SiemOptions {
+    vendor: String,
+    product: String,
+    version: String,
+    signature_id: Option<String>,
+    severity_map: HashMap<String, u8>,
+    extension_map: HashMap<String, String>,
+}
+
+impl SiemOptions {
+    fn from(opts: &FormatOptions) -> Self {
+        let get = |key: &str| opts.as_ref().and_then(|o| o.get(key));
+        let text = |key: &str, default: &str| get(key).map_or(default, String::as_str).to_string();
+
+        SiemOptions {
+            vendor: text("vendor", "logform"),
+            product: text("product", "logform"),
+            version: text("version", env!("CARGO_PKG_VERSION")),
+            signature_id: get("signature_id").cloned(),
+            severity_map: get("severity_map")
+                .and_then(|raw| serde_json::from_str(raw).ok())
+                .unwrap_or_default(),
+            extension_map: get("extension_map")
+                .and_then(|raw| serde_json::from_str(raw).ok())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Severity on the 0-10 scale used by both formats.
+    fn severity(&self, level: &str) -> u8 {
+        if let Some(severity) = self.severity_map.get(level) {
+            return (*severity).min(10);
+        }
+        match syslog_severity(level) {
+            Some(0) | Some(1) => 10,
+            Some(2) => 9,
+            Some(3) => 7,
+            Some(4) => 5,
+            Some(5) => 4,
+            Some(6) => 3,
+            _ => 1,
+        }
+    }
+
+    /// The event id: a `signature_id` meta, the `signature_id` option, or the level.
+    fn signature_id(&self, info: &LogInfo) -> String {
+        info.meta
+            .get("signature_id")
+            .map(value_to_text)
+            .or_else(|| self.signature_id.clone())
+            .unwrap_or_else(|| info.level.clone())
+    }
+
+    /// Extension key/value pairs from the meta, renamed through `extension_map`,
+    /// with the `timestamp` meta as epoch milliseconds under `time_key`. Names are
+    /// unique: one already taken, by `reserved` or an earlier key, gets a `_1`,
+    /// `_2`, ... suffix.
+    fn extensions(
+        &self,
+        info: &LogInfo,
+        time_key: &str,
+        reserved: &[&str],
+    ) -> Vec<(String, String)> {
+        let mut keys: Vec<&String> = info.meta.keys().collect();
+        keys.sort();
+
+        let mut extensions = Vec::new();
+        let mut used: Vec<String> = reserved.iter().map(|k| k.to_string()).collect();
+        if let Some(time) = record_time(info, "timestamp") {
+            extensions.push((time_key.to_string(), time.timestamp_millis().to_string()));
+            used.push(time_key.to_string());
+        }
+
+        for key in keys {
+            if key == "timestamp" || key == "signature_id" || key == "splat" {
+                continue;
+            }
+            let name = self
+                .extension_map
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| extension_key(key));
+            if name.is_empty() {
+                continue;
+            }
+            let mut unique = name.clone();
+            let mut n = 0;
+            while used.contains(&unique) {
+                n += 1;
+                unique = format!("{}_{}", name, n);
+            }
+            used.push(unique.clone());
+            extensions.push((unique, value_to_text(&info.meta[key])));
+        }
+        extensions
+    }
+}
+
+/// Extension keys may only contain letters, digits and underscores.
+fn extension_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect()
+}
+
+/// Header fields escape backslashes and pipes; line breaks are not allowed.
+fn escape_header(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+/// Extension values escape backslashes, equal signs and line breaks.
+fn escape_extension(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+/// LEEF defines no escapes: line breaks become spaces and the delimiter is written
+/// as `\t` (tab) or preceded by a backslash, as QRadar expects.
+fn escape_leef(value: &str, delimiter: char) -> String {
+    let escaped_delimiter = if delimiter == '\t' {
+        "\\t".to_string()
+    } else {
+        format!("\\{}", delimiter)
+    };
+    value
+        .replace("\r\n", " ")
+        .replace(['\r', '\n'], " ")
+        .replace(delimiter, &escaped_delimiter)
+}
+
+/// Renders the log info as an ArcSight CEF event:
+/// `CEF:0|vendor|product|version|signatureId|name|severity|extensions`.
+pub fn cef() -> Format {
+    Format::new(|mut info: LogInfo, opts: FormatOptions| {
+        let options = SiemOptions::from(&opts);
+
+        let header = [
+            options.vendor.as_str(),
+            options.product.as_str(),
+            options.version.as_str(),
+            &options.signature_id(&info),
+            &info.message,
+            &options.severity(&info.level).to_string(),
+        ]
+        .map(escape_header)
+        .join("|");
+
+        let extensions: Vec<String> = options
+            .extensions(&info, "rt", &[])
+            .into_iter()
+            .map(|(k, v)| format!("{}={}", k, escape_extension(&v)))
+            .collect();
+
+        info.message = format!("CEF:0|{}|{}", header, extensions.join(" "));
+        Some(info)
+    })
+}
+
+/// Renders the log info as a QRadar LEEF 2.0 event:
+/// `LEEF:2.0|vendor|product|version|eventId|delimiter|attributes`.
+pub fn leef() -> Format {
+    Format::new(|mut info: LogInfo, opts: FormatOptions| {
+        let options = SiemOptions::from(&opts);
+        let delimiter = opts
+            .as_ref()
+            .and_then(|o| o.get("delimiter"))
+            .and_then(|d| d.chars().next())
+            .unwrap_or('\t');
+
+        let header = [
+            options.vendor.as_str(),
+            options.product.as_str(),
+            options.version.as_str(),
+            &options.signature_id(&info),
+        ]
+        .map(escape_header)
+        .join("|");
+
+        // The tab is the default delimiter; anything else is declared in the header
+        let declared = if delimiter == '\t' {
+            String::new()
+        } else {
+            delimiter.to_string()
+        };
+
+        let mut attributes = vec![
+            ("sev".to_string(), options.severity(&info.level).to_string()),
+            ("msg".to_string(), info.message.clone()),
+        ];
+        attributes.extend(options.extensions(&info, "devTime", &["sev", "msg"]));
+
+        let attributes: Vec<String> = attributes
+            .into_iter()
+            .map(|(k, v)| format!("{}={}", k, escape_leef(&v, delimiter)))
+            .collect();
+
+        info.message = format!(
+            "LEEF:2.0|{}|{}|{}",
+            header,
+            declared,
+            attributes.join(&delimiter.to_string())
+        );
+        Some(info)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cef_formatter() {
+        let formatter = cef()
+            .with_option("vendor", "Acme")
+            .with_option("product", "Pay|Gate")
+            .with_option("version", "1.0")
+            .with_option("extension_map", r#"{"user": "suser", "ip": "src"}"#);
+
+        let info = LogInfo::new("warn", "Login failed")
+            .add_meta("timestamp", "2024-08-27T02:39:15Z")
+            .add_meta("signature_id", "AUTH-401")
+            .add_meta("user", "bob")
+            .add_meta("ip", "10.0.0.1")
+            .add_meta("query", "a=b\\c\nd");
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        assert_eq!(
+            result.message,
+            r"CEF:0|Acme|Pay\|Gate|1.0|AUTH-401|Login failed|5|rt=1724726355000 src=10.0.0.1 query=a\=b\\c\nd suser=bob"
+        );
+    }
+
+    #[test]
+    fn test_siem_duplicate_extension_names() {
+        let info = LogInfo::new("info", "hi")
+            .add_meta("a.b", 1)
+            .add_meta("ab", 2)
+            .add_meta("ip", "10.0.0.1")
+            .add_meta("src", "gateway")
+            .add_meta("sev", "high");
+
+        let result = cef()
+            .with_option("extension_map", r#"{"ip": "src"}"#)
+            .transform(info.clone(), None)
+            .unwrap();
+        assert!(result
+            .message
+            .ends_with("|ab=1 ab_1=2 src=10.0.0.1 sev=high src_1=gateway"));
+
+        let result = leef().transform(info, None).unwrap();
+        assert!(result
+            .message
+            .ends_with("|sev=3\tmsg=hi\tab=1\tab_1=2\tip=10.0.0.1\tsev_1=high\tsrc=gateway"));
+    }
+
+    #[test]
+    fn test_leef_formatter() {
+        let formatter = leef()
+            .with_option("vendor", "Acme")
+            .with_option("product", "PayGate")
+            .with_option("version", "1.0")
+            .with_option("severity_map", r#"{"error": 9}"#);
+
+        let info = LogInfo::new("error", "Card declined")
+            .add_meta("card", "visa")
+            .add_meta("note", "tab\there")
+            .add_meta("query", "a=b\\c\nd");
+
+        let result = formatter.transform(info.clone(), None).unwrap();
+        assert_eq!(
+            result.message,
+            "LEEF:2.0|Acme|PayGate|1.0|error||sev=9\tmsg=Card declined\tcard=visa\tnote=tab\\there\tquery=a=b\\c d"
+        );
+
+        let result = formatter
+            .with_option("delimiter", "^")
+            .transform(info, None)
+            .unwrap();
+        assert_eq!(
+            result.message,
+            "LEEF:2.0|Acme|PayGate|1.0|error|^|sev=9^msg=Card declined^card=visa^note=tab\there^query=a=b\\c d"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use crate::utils::fields::value_to_text;
+use crate::utils::host::hostname;
+use crate::utils::time::record_time_or_now;
+use crate::{Format, FormatOptions, LogInfo};
+use serde_json::{json, Map, Value};
+
+fn hec_event(info: &LogInfo, opts: &FormatOptions) -> Value {
+    let opt = |key: &str| opts.as_ref().and_then(|o| o.get(key));
+
+    let time = record_time_or_now(info, "timestamp");
+
+    let mut event = Map::new();
+    for (key, value) in &info.meta {
+        event.insert(key.clone(), value.clone());
+    }
+    event.remove("timestamp");
+    event.remove("splat");
+    event.insert("level".to_string(), Value::String(info.level.clone()));
+    event.insert("message".to_string(), Value::String(info.message.clone()));
+
+    let mut payload = Map::new();
+    // Epoch seconds with millisecond precision
+    payload.insert(
+        "time".to_string(),
+        json!(time.timestamp_millis() as f64 / 1000.0),
+    );
+    payload.insert(
+        "host".to_string(),
+        json!(opt("host").map_or(hostname(), String::as_str)),
+    );
+    for key in ["source", "index"] {
+        if let Some(value) = opt(key) {
+            payload.insert(key.to_string(), json!(value));
+        }
+    }
+    payload.insert(
+        "sourcetype".to_string(),
+        json!(opt("sourcetype").map_or("_json", String::as_str)),
+    );
+    payload.insert("event".to_string(), Value::Object(event));
+
+    // Indexed fields only accept strings or arrays of strings
+    if let Some(keys) = opt("fields") {
+        let fields: Map<String, Value> = keys
+            .split(',')
+            .map(str::trim)
+            .filter_map(|key| {
+                let value = match info.meta.get(key)? {
+                    Value::Array(items) => {
+                        Value::Array(items.iter().map(|v| json!(value_to_text(v))).collect())
+                    }
+                    other => json!(value_to_text(other)),
+                };
+                Some((key.to_string(), value))
+            })
+            .collect();
+        if !fields.is_empty() {
+            payload.insert("fields".to_string(), Value::Object(fields));
+        }
+    }
+
+    Value::Object(payload)
+}
+
+/// Wraps the log info as a Splunk HTTP Event Collector event.
+pub fn splunk_hec() -> Format {
+    Format::new(|info: LogInfo, opts: FormatOptions| {
+        let event = hec_event(&info, &opts).to_string();
+        Some(LogInfo {
+            level: info.level,
+            message: event,
+            meta: info.meta,
+        })
+    })
+}
+
+/// Encodes a batch of records as a HEC request body: the events concatenated one
+/// after another, without separators.
+pub fn splunk_hec_batch(records: &[LogInfo], opts: FormatOptions) -> String {
+    records
+        .iter()
+        .map(|record| hec_event(record, &opts).to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_splunk_hec_formatter() {
+        let formatter = splunk_hec()
+            .with_option("host", "web-1")
+            .with_option("source", "checkout")
+            .with_option("index", "main")
+            .with_option("fields", "region, tags, missing");
+
+        let info = LogInfo::new("warn", "Slow request")
+            .add_meta("timestamp", "2024-08-27T02:39:15.250Z")
+            .add_meta("region", "eu-west-1")
+            .add_meta("tags", json!(["a", 1]))
+            .add_meta("duration_ms", 1503);
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        let event: Value = serde_json::from_str(&result.message).unwrap();
+        assert_eq!(
+            event,
+            json!({
+                "time": 1724726355.25,
+                "host": "web-1",
+                "source": "checkout",
+                "sourcetype": "_json",
+                "index": "main",
+                "event": {
+                    "level": "warn",
+                    "message": "Slow request",
+                    "region": "eu-west-1",
+                    "tags": ["a", 1],
+                    "duration_ms": 1503,
+                },
+                "fields": {"region": "eu-west-1", "tags": ["a", "1"]},
+            })
+        );
+    }
+
+    #[test]
+    fn test_splunk_hec_batch() {
+        let records = vec![
+            LogInfo::new("info", "first"),
+            LogInfo::new("info", "second"),
+        ];
+        let opts = HashMap::from([("host".to_string(), "web-1".to_string())]);
+
+        let body = splunk_hec_batch(&records, Some(opts));
+
+        let events: Vec<Value> = serde_json::Deserializer::from_str(&body)
+            .into_iter::<Value>()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1]["event"]["message"], "second");
+        assert!(body.contains("}{"));
+    }
+}
@@ -17,7 +17,7 @@ pub fn timestamp() -> Format {
             .unwrap_or("timestamp");
 
         let now: DateTime<Utc> = Utc::now();
-        let timestamp = now.format(&format).to_string();
+        let timestamp = now.format(format).to_string();
 
         info.meta.insert(alias.to_string(), json!(timestamp));
         Some(info)
@@ -0,0 +1,89 @@
+use crate::json::JsonOptions;
+use crate::{Format, FormatOptions, LogInfo};
+use serde_json::{json, Value};
+
+/// Makes a value representable in TOML: it has no null, so null values are left out,
+/// and integers beyond the signed 64-bit range are written as strings.
+fn toml_compatible(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k, toml_compatible(v)))
+                .collect(),
+        ),
+        Value::Array(arr) => Value::Array(
+            arr.into_iter()
+                .filter(|v| !v.is_null())
+                .map(toml_compatible)
+                .collect(),
+        ),
+        Value::Number(n) if n.is_u64() && n.as_i64().is_none() => Value::String(n.to_string()),
+        other => other,
+    }
+}
+
+/// Renders the log info as a TOML document, with the same keys and key options
+/// as `json()`. Null values, which TOML can't represent, are left out and integers
+/// above `i64::MAX` are written as strings. Should the document still fail to
+/// serialize, the level and message are written with the error under `error`.
+pub fn toml() -> Format {
+    Format::new(|info: LogInfo, opts: FormatOptions| {
+        let options = JsonOptions::from(&opts);
+        let log_object = toml_compatible(Value::Object(options.build_object(&info)));
+
+        let document = toml::to_string(&log_object).unwrap_or_else(|e| {
+            let fallback = json!({
+                options.level_key.as_str(): info.level,
+                options.message_key.as_str(): info.message,
+                "error": e.to_string(),
+            });
+            toml::to_string(&fallback).unwrap_or_default()
+        });
+
+        Some(LogInfo {
+            level: info.level,
+            message: document.trim_end().to_string(),
+            meta: info.meta,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_formatter() {
+        let formatter = toml();
+
+        let info = LogInfo::new("info", "Config loaded")
+            .add_meta("ports", json!([80, null, 443]))
+            .add_meta("tls", json!({"enabled": true, "cert": null}))
+            .add_meta("ratio", 0.5);
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        assert_eq!(
+            result.message,
+            "level = \"info\"\nmessage = \"Config loaded\"\nports = [80, 443]\nratio = 0.5\n\n[tls]\nenabled = true"
+        );
+
+        let parsed: Value = toml::from_str(&result.message).unwrap();
+        assert_eq!(parsed["tls"]["enabled"], true);
+    }
+
+    #[test]
+    fn test_toml_large_integers() {
+        let info = LogInfo::new("info", "disk")
+            .add_meta("inodes", u64::MAX)
+            .add_meta("sizes", json!([1, u64::MAX]));
+
+        let result = toml().transform(info, None).unwrap();
+        assert_eq!(
+            result.message,
+            "inodes = \"18446744073709551615\"\nlevel = \"info\"\nmessage = \"disk\"\nsizes = [1, \"18446744073709551615\"]"
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{Format, FormatOptions, LogInfo};
+use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
 
@@ -19,10 +20,16 @@ pub fn uncolorize() -> Format {
     })
 }
 
-fn strip_colors(input: &str) -> String {
+lazy_static! {
     // Regex pattern to match ANSI escape codes
-    let re = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
-    re.replace_all(input, "").to_string()
+    static ref ANSI_COLOR: Regex = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
+}
+
+pub(crate) fn strip_colors(input: &str) -> String {
+    if !input.contains('\x1b') {
+        return input.to_string();
+    }
+    ANSI_COLOR.replace_all(input, "").to_string()
 }
 
 #[cfg(test)]
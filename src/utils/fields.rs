@@ -0,0 +1,41 @@
+use crate::LogInfo;
+use serde_json::Value;
+
+/// Resolves a column/field name against a `LogInfo`.
+///
+/// `level` and `message` refer to the core fields, `meta.<path>` (or a bare
+/// `<path>`) looks up meta, descending into nested objects on each `.`.
+pub fn lookup_field(info: &LogInfo, name: &str) -> Option<Value> {
+    match name {
+        "level" => Some(Value::String(info.level.clone())),
+        "message" => Some(Value::String(info.message.clone())),
+        _ => {
+            let path = name.strip_prefix("meta.").unwrap_or(name);
+            let mut parts = path.split('.');
+            let mut current = info.meta.get(parts.next()?)?;
+            for part in parts {
+                current = current.get(part)?;
+            }
+            Some(current.clone())
+        }
+    }
+}
+
+/// Returns the top-level meta key a field name refers to, if any.
+pub fn meta_root(name: &str) -> Option<&str> {
+    match name {
+        "level" | "message" => None,
+        _ => {
+            let path = name.strip_prefix("meta.").unwrap_or(name);
+            path.split('.').next()
+        }
+    }
+}
+
+/// Renders a value as plain text: strings without quotes, everything else as JSON.
+pub fn value_to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,22 @@
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref HOSTNAME: String = gethostname::gethostname().to_string_lossy().into_owned();
+}
+
+/// The name of the current host, looked up once.
+pub fn hostname() -> &'static str {
+    &HOSTNAME
+}
+
+lazy_static! {
+    static ref PROCESS_NAME: String = std::env::current_exe()
+        .ok()
+        .and_then(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "app".to_string());
+}
+
+/// The file name of the running executable, without extension.
+pub fn process_name() -> &'static str {
+    &PROCESS_NAME
+}
@@ -0,0 +1,47 @@
+use crate::uncolorize::strip_colors;
+
+/// Maps a level name to its RFC 5424 syslog severity (0 = emergency … 7 = debug).
+///
+/// Both the npm levels used by default (`error`, `warn`, `info`, `http`, `verbose`,
+/// `debug`, `silly`) and the syslog levels are understood. Colorized levels are
+/// stripped first so this works after `colorize()`. Unknown levels return `None`.
+pub fn syslog_severity(level: &str) -> Option<u8> {
+    plain_severity(&plain_level(level))
+}
+
+/// Same as `syslog_severity`, for a level already passed through `plain_level`.
+pub fn plain_severity(plain: &str) -> Option<u8> {
+    let severity = match plain {
+        "emerg" | "emergency" | "panic" => 0,
+        "alert" => 1,
+        "crit" | "critical" | "fatal" => 2,
+        "error" | "err" => 3,
+        "warn" | "warning" => 4,
+        "notice" => 5,
+        "info" | "http" => 6,
+        "verbose" | "debug" | "silly" | "trace" => 7,
+        _ => return None,
+    };
+    Some(severity)
+}
+
+/// Returns the level name without color codes and in lowercase.
+pub fn plain_level(level: &str) -> String {
+    strip_colors(level).to_lowercase()
+}
+
+/// Maps a level to the numeric levels used by bunyan and pino
+/// (10 = trace, 20 = debug, 30 = info, 40 = warn, 50 = error, 60 = fatal).
+pub fn bunyan_level(level: &str) -> u8 {
+    let level = plain_level(level);
+    if matches!(level.as_str(), "silly" | "trace") {
+        return 10;
+    }
+    match plain_severity(&level) {
+        Some(0..=2) => 60,
+        Some(3) => 50,
+        Some(4) => 40,
+        Some(7) => 20,
+        _ => 30,
+    }
+}
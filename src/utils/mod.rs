@@ -1 +1,7 @@
+pub mod fields;
 pub mod format_json;
+pub mod host;
+pub mod levels;
+pub mod record;
+pub mod safe_json;
+pub mod time;
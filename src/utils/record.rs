@@ -0,0 +1,70 @@
+use crate::{DecodeError, LogInfo};
+use serde_json::Value;
+
+/// Added to meta keys that would clash with `level` or `message`, as with `json()`'s
+/// `collision=prefix`.
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+const COLLISION_PREFIX: &str = "meta_";
+
+/// Whether a meta key is `level` or `message` behind any number of prefixes. Those
+/// keys get one more prefix when encoded and lose one when decoded, so `level`,
+/// `meta_level`, ... all survive a round trip.
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+fn is_escaped_core_key(key: &str) -> bool {
+    let mut key = key;
+    while let Some(rest) = key.strip_prefix(COLLISION_PREFIX) {
+        key = rest;
+    }
+    key == "level" || key == "message"
+}
+
+/// The record as `json()` shapes it: level, message and meta at the top level. Meta
+/// keys that clash with the level or message are prefixed with `meta_`.
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+pub fn encode_object(info: &LogInfo) -> Value {
+    let mut object = serde_json::Map::new();
+    for (key, value) in &info.meta {
+        let key = if is_escaped_core_key(key) {
+            format!("{}{}", COLLISION_PREFIX, key)
+        } else {
+            key.clone()
+        };
+        object.insert(key, value.clone());
+    }
+    object.insert("level".to_string(), Value::String(info.level.clone()));
+    object.insert("message".to_string(), Value::String(info.message.clone()));
+    Value::Object(object)
+}
+
+/// Reads a record shaped by `json()` with its default keys.
+pub fn from_object(value: Value) -> Result<LogInfo, DecodeError> {
+    let Value::Object(mut map) = value else {
+        return Err(DecodeError::NotAnObject);
+    };
+
+    let mut take_string = |field: &'static str| match map.remove(field) {
+        Some(Value::String(s)) => Ok(s),
+        _ => Err(DecodeError::MissingField(field)),
+    };
+    let level = take_string("level")?;
+    let message = take_string("message")?;
+
+    let mut info = LogInfo::new(level, message);
+    info.meta.extend(map);
+    Ok(info)
+}
+
+/// Reverses `encode_object`.
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+pub fn decode_object(value: Value) -> Result<LogInfo, DecodeError> {
+    let mut info = from_object(value)?;
+    info.meta = info
+        .meta
+        .into_iter()
+        .map(|(key, value)| match key.strip_prefix(COLLISION_PREFIX) {
+            Some(unprefixed) if is_escaped_core_key(&key) => (unprefixed.to_string(), value),
+            _ => (key, value),
+        })
+        .collect();
+    Ok(info)
+}
@@ -0,0 +1,348 @@
+use crate::FormatOptions;
+use serde_json::{Map, Value};
+
+const DEFAULT_MAX_DEPTH: usize = 10;
+const DEFAULT_MAX_STRING_LENGTH: usize = 8 * 1024;
+const DEFAULT_MAX_ARRAY_LENGTH: usize = 100;
+const DEFAULT_MAX_BYTES: usize = 64 * 1024;
+
+/// Limits applied to a record before serialization, so a huge or deeply nested
+/// meta value cannot blow up a log line.
+///
+/// Each limit is enabled by its own option (`max_depth`, `max_string_length`,
+/// `max_array_length`, `max_bytes`); `safe=true` enables all of them, with
+/// defaults for those not given. Whatever is cut is replaced by a marker
+/// saying what was cut.
+#[derive(Clone, Debug, Default)]
+pub struct SafeLimits {
+    pub max_depth: Option<usize>,
+    pub max_string_length: Option<usize>,
+    pub max_array_length: Option<usize>,
+    pub max_bytes: Option<usize>,
+}
+
+impl SafeLimits {
+    pub fn from(opts: &FormatOptions) -> Option<Self> {
+        let get = |key: &str| opts.as_ref().and_then(|o| o.get(key));
+        let safe = get("safe").is_some_and(|v| v == "true");
+        let limit = |key: &str, default: usize| {
+            get(key)
+                .and_then(|v| v.parse().ok())
+                .or(if safe { Some(default) } else { None })
+        };
+
+        let limits = SafeLimits {
+            max_depth: limit("max_depth", DEFAULT_MAX_DEPTH),
+            max_string_length: limit("max_string_length", DEFAULT_MAX_STRING_LENGTH),
+            max_array_length: limit("max_array_length", DEFAULT_MAX_ARRAY_LENGTH),
+            max_bytes: limit("max_bytes", DEFAULT_MAX_BYTES),
+        };
+
+        if limits.max_depth.is_none()
+            && limits.max_string_length.is_none()
+            && limits.max_array_length.is_none()
+            && limits.max_bytes.is_none()
+        {
+            None
+        } else {
+            Some(limits)
+        }
+    }
+
+    /// Applies the depth, string and array limits to every field of a record
+    /// (the record itself being at depth 1) except the level, then shrinks it until
+    /// `render` produces at most `max_bytes`. `level_key` and `message_key` name the
+    /// core fields, which are shrunk last.
+    pub fn apply<F>(
+        &self,
+        record: Map<String, Value>,
+        level_key: &str,
+        message_key: &str,
+        render: F,
+    ) -> Map<String, Value>
+    where
+        F: Fn(&Map<String, Value>) -> String,
+    {
+        let record: Map<String, Value> = record
+            .into_iter()
+            .map(|(k, v)| {
+                // The level is never changed, downstream routing depends on it
+                let limited = if k == level_key { v } else { self.limit(v, 2) };
+                (k, limited)
+            })
+            .collect();
+
+        match self.max_bytes {
+            Some(max_bytes) => fit(record, max_bytes, level_key, message_key, render),
+            None => record,
+        }
+    }
+
+    fn limit(&self, value: Value, depth: usize) -> Value {
+        match value {
+            Value::String(s) => match self.max_string_length {
+                Some(max) => Value::String(truncate_string(s, max)),
+                None => Value::String(s),
+            },
+            Value::Object(map) if self.too_deep(depth) => {
+                Value::String(format!("[Truncated: object with {} keys]", map.len()))
+            }
+            Value::Array(arr) if self.too_deep(depth) => {
+                Value::String(format!("[Truncated: array with {} items]", arr.len()))
+            }
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| {
+                        let limited = self.limit(v, depth + 1);
+                        (k, limited)
+                    })
+                    .collect(),
+            ),
+            Value::Array(arr) => {
+                let total = arr.len();
+                let keep = self.max_array_length.unwrap_or(total).min(total);
+                let mut items: Vec<Value> = arr
+                    .into_iter()
+                    .take(keep)
+                    .map(|v| self.limit(v, depth + 1))
+                    .collect();
+                if keep < total {
+                    items.push(Value::String(format!(
+                        "[Truncated: {} more items]",
+                        total - keep
+                    )));
+                }
+                Value::Array(items)
+            }
+            other => other,
+        }
+    }
+
+    fn too_deep(&self, depth: usize) -> bool {
+        self.max_depth.is_some_and(|max| depth > max)
+    }
+}
+
+fn truncate_string(s: String, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars) {
+        None => s,
+        Some((cut, _)) => {
+            let dropped = s[cut..].chars().count();
+            format!("{}...[Truncated: {} more chars]", &s[..cut], dropped)
+        }
+    }
+}
+
+/// Shrinks a record until the rendered record fits:
+///
+/// 1. the largest meta fields are replaced with markers,
+/// 2. meta fields are removed, largest first, noting how many under `_truncated`,
+/// 3. the message is cut, keeping its start,
+/// 4. as a last resort only the level and a marker for the message are kept.
+///
+/// The level is never changed, so a record whose level alone exceeds `max_bytes`
+/// can't be made to fit.
+fn fit<F>(
+    mut record: Map<String, Value>,
+    max_bytes: usize,
+    level_key: &str,
+    message_key: &str,
+    render: F,
+) -> Map<String, Value>
+where
+    F: Fn(&Map<String, Value>) -> String,
+{
+    let original_size = render(&record).len();
+    if original_size <= max_bytes {
+        return record;
+    }
+    let fits = |record: &Map<String, Value>| render(record).len() <= max_bytes;
+
+    let mut fields: Vec<(String, usize)> = record
+        .iter()
+        .filter(|(k, _)| *k != level_key && *k != message_key)
+        .map(|(k, v)| (k.clone(), v.to_string().len()))
+        .collect();
+    fields.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    for (key, field_size) in &fields {
+        let marker = format!("[Truncated: {} bytes]", field_size);
+        if marker.len() >= *field_size {
+            continue;
+        }
+        record.insert(key.clone(), Value::String(marker));
+        if fits(&record) {
+            return record;
+        }
+    }
+
+    let mut removed = 0;
+    for (key, _) in &fields {
+        record.remove(key);
+        removed += 1;
+        record.insert(
+            "_truncated".to_string(),
+            Value::String(format!("[Truncated: {} fields]", removed)),
+        );
+        if fits(&record) {
+            return record;
+        }
+    }
+
+    if let Some(Value::String(message)) = record.get(message_key).cloned() {
+        let mut keep = message.chars().count();
+        while keep > 0 {
+            let overflow = render(&record).len().saturating_sub(max_bytes);
+            if overflow == 0 {
+                return record;
+            }
+            // Account for the marker the first cut adds
+            keep = keep.saturating_sub(overflow.max(1) + 32);
+            record.insert(
+                message_key.to_string(),
+                Value::String(truncate_string(message.clone(), keep)),
+            );
+        }
+        if fits(&record) {
+            return record;
+        }
+    }
+
+    let mut minimal = Map::new();
+    if let Some(level) = record.remove(level_key) {
+        minimal.insert(level_key.to_string(), level);
+    }
+    minimal.insert(
+        message_key.to_string(),
+        Value::String(format!("[Truncated: {} bytes]", original_size)),
+    );
+    minimal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn limits(depth: usize, string: usize, array: usize) -> SafeLimits {
+        SafeLimits {
+            max_depth: Some(depth),
+            max_string_length: Some(string),
+            max_array_length: Some(array),
+            max_bytes: None,
+        }
+    }
+
+    #[test]
+    fn test_safe_limits() {
+        let record = json!({
+            "nested": {"a": {"b": {"c": 1}}},
+            "text": "héllo world",
+            "list": [1, 2, 3, 4, 5],
+        });
+        let Value::Object(record) = record else {
+            unreachable!()
+        };
+
+        let limited = limits(3, 4, 2).apply(record, "level", "message", |r| {
+            Value::Object(r.clone()).to_string()
+        });
+        assert_eq!(
+            Value::Object(limited),
+            json!({
+                "nested": {"a": {"b": "[Truncated: object with 1 keys]"}},
+                "text": "héll...[Truncated: 7 more chars]",
+                "list": [1, 2, "[Truncated: 3 more items]"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_safe_limits_keep_level() {
+        let record = json!({"level": "info", "message": "hello", "user": "bob"});
+        let Value::Object(record) = record else {
+            unreachable!()
+        };
+
+        let limited = limits(3, 2, 2).apply(record, "level", "message", render);
+        assert_eq!(limited["level"], "info");
+        assert_eq!(limited["message"], "he...[Truncated: 3 more chars]");
+        assert_eq!(limited["user"], "bo...[Truncated: 1 more chars]");
+    }
+
+    #[test]
+    fn test_safe_max_bytes() {
+        let record = json!({
+            "message": "short",
+            "big": "x".repeat(500),
+            "small": 1,
+        });
+        let Value::Object(record) = record else {
+            unreachable!()
+        };
+
+        let limits = SafeLimits {
+            max_bytes: Some(100),
+            ..Default::default()
+        };
+        let limited = limits.apply(record, "level", "message", render);
+        assert_eq!(limited["big"], "[Truncated: 502 bytes]");
+        assert_eq!(limited["message"], "short");
+        assert!(Value::Object(limited).to_string().len() <= 100);
+    }
+
+    fn render(record: &Map<String, Value>) -> String {
+        Value::Object(record.clone()).to_string()
+    }
+
+    fn max_bytes(max: usize) -> SafeLimits {
+        SafeLimits {
+            max_bytes: Some(max),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_safe_max_bytes_many_small_fields() {
+        let mut record = Map::new();
+        record.insert("level".to_string(), json!("info"));
+        record.insert("message".to_string(), json!("hello"));
+        for i in 0..50 {
+            record.insert(format!("field{:02}", i), json!(i));
+        }
+        assert!(render(&record).len() > 400);
+
+        let limited = max_bytes(100).apply(record, "level", "message", render);
+        let output = render(&limited);
+        assert!(output.len() <= 100, "{}", output);
+        assert_eq!(limited["level"], "info");
+        assert_eq!(limited["message"], "hello");
+        assert!(limited["_truncated"]
+            .as_str()
+            .unwrap()
+            .starts_with("[Truncated: "));
+    }
+
+    #[test]
+    fn test_safe_max_bytes_long_message() {
+        let mut record = Map::new();
+        record.insert("level".to_string(), json!("error"));
+        record.insert("message".to_string(), json!("y".repeat(3000)));
+        record.insert("user".to_string(), json!("bob"));
+
+        let limited = max_bytes(200).apply(record.clone(), "level", "message", render);
+        let output = render(&limited);
+        assert!(output.len() <= 200, "{}", output);
+        assert_eq!(limited["level"], "error");
+        let message = limited["message"].as_str().unwrap();
+        assert!(message.starts_with("yyyy"));
+        assert!(message.contains("...[Truncated: "));
+
+        // Too small for any message at all
+        let limited = max_bytes(40).apply(record, "level", "message", render);
+        assert_eq!(
+            Value::Object(limited),
+            json!({"level": "error", "message": "[Truncated: 3043 bytes]"})
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use crate::LogInfo;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde_json::Value;
+
+/// Reads the time of a record from the given meta key.
+///
+/// Strings are parsed as RFC 3339 or as the `timestamp()` default format
+/// (`%Y-%m-%d %H:%M:%S`, taken as UTC); numbers are epoch milliseconds.
+pub fn record_time(info: &LogInfo, key: &str) -> Option<DateTime<Utc>> {
+    match info.meta.get(key)? {
+        Value::String(s) => parse_time(s),
+        Value::Number(n) => {
+            let millis = n.as_f64()?;
+            Utc.timestamp_millis_opt(millis as i64).single()
+        }
+        _ => None,
+    }
+}
+
+/// Same as `record_time`, falling back to the current time.
+pub fn record_time_or_now(info: &LogInfo, key: &str) -> DateTime<Utc> {
+    record_time(info, key).unwrap_or_else(Utc::now)
+}
+
+pub fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .map(|naive| naive.and_utc())
+}
+
+/// Nanoseconds since the Unix epoch, clamped to zero for pre-epoch times.
+pub fn unix_nanos(time: &DateTime<Utc>) -> u64 {
+    time.timestamp_nanos_opt().unwrap_or(0).max(0) as u64
+}
@@ -0,0 +1,154 @@
+use crate::utils::fields::{lookup_field, value_to_text};
+use crate::utils::time::record_time_or_now;
+use crate::{Format, FormatOptions, LogInfo};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const DEFAULT_FIELDS: &str = "date time cs-method cs-uri-stem sc-status";
+
+fn fields(opts: &FormatOptions) -> Vec<String> {
+    opts.as_ref()
+        .and_then(|o| o.get("fields"))
+        .map_or(DEFAULT_FIELDS, String::as_str)
+        .split([' ', ','])
+        .filter(|f| !f.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn header(fields: &[String], date: DateTime<Utc>) -> String {
+    format!(
+        "#Version: 1.0\n#Fields: {}\n#Date: {}",
+        fields.join(" "),
+        date.format("%Y-%m-%d %H:%M:%S")
+    )
+}
+
+/// Resolves a W3C field identifier, mapping the standard ones to the same request
+/// meta `access_log()` uses and looking anything else up in the log info.
+fn field_value(info: &LogInfo, field: &str) -> Option<String> {
+    let key = match field {
+        "date" => {
+            return Some(
+                record_time_or_now(info, "timestamp")
+                    .format("%Y-%m-%d")
+                    .to_string(),
+            )
+        }
+        "time" => {
+            return Some(
+                record_time_or_now(info, "timestamp")
+                    .format("%H:%M:%S")
+                    .to_string(),
+            )
+        }
+        "c-ip" => "remote_addr",
+        "cs-username" => "remote_user",
+        "cs-method" => "method",
+        "cs-uri-stem" => "path",
+        "cs-uri-query" => "query",
+        "cs-version" => "protocol",
+        "sc-status" => "status",
+        "sc-bytes" => "bytes",
+        "time-taken" => "duration_ms",
+        "cs(User-Agent)" => "user_agent",
+        "cs(Referer)" => "referer",
+        other => other,
+    };
+
+    match lookup_field(info, key)? {
+        Value::Null => None,
+        value => Some(value_to_text(&value)),
+    }
+}
+
+/// Fields are separated by spaces, so spaces inside values are written as `+`.
+fn escape(value: &str) -> String {
+    if value.is_empty() {
+        return "-".to_string();
+    }
+    value
+        .chars()
+        .map(|c| if c.is_whitespace() { '+' } else { c })
+        .collect()
+}
+
+pub fn w3c_extended() -> Format {
+    let header_written = Arc::new(AtomicBool::new(false));
+
+    Format::new(move |mut info: LogInfo, opts: FormatOptions| {
+        let fields = fields(&opts);
+
+        let row = fields
+            .iter()
+            .map(|field| field_value(&info, field).map_or("-".to_string(), |v| escape(&v)))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        // The directives are written once, before the first row
+        let with_header = opts
+            .as_ref()
+            .and_then(|o| o.get("header"))
+            .map(String::as_str)
+            != Some("false");
+
+        info.message = if with_header && !header_written.swap(true, Ordering::SeqCst) {
+            let date = record_time_or_now(&info, "timestamp");
+            format!("{}\n{}", header(&fields, date), row)
+        } else {
+            row
+        };
+        Some(info)
+    })
+}
+
+/// Returns the directive lines (`#Version`, `#Fields`, `#Date`) for the given
+/// `w3c_extended()` format, e.g. to start a new file after rotation.
+pub fn w3c_extended_header(format: &Format) -> String {
+    header(&fields(&format.options), Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_w3c_extended_formatter() {
+        let formatter = w3c_extended().with_option(
+            "fields",
+            "date time c-ip cs-method cs-uri-stem sc-status cs(User-Agent) cs(Referer)",
+        );
+
+        let request = |path: &str| {
+            LogInfo::new("info", "request handled")
+                .add_meta("timestamp", "2024-08-27T02:39:15Z")
+                .add_meta("remote_addr", "10.0.0.1")
+                .add_meta("method", "GET")
+                .add_meta("path", path)
+                .add_meta("status", 200)
+                .add_meta("user_agent", "Mozilla/5.0 (X11; Linux)")
+        };
+
+        let first = formatter.transform(request("/"), None).unwrap();
+        println!("{}", first.message);
+        assert_eq!(
+            first.message,
+            "#Version: 1.0\n\
+             #Fields: date time c-ip cs-method cs-uri-stem sc-status cs(User-Agent) cs(Referer)\n\
+             #Date: 2024-08-27 02:39:15\n\
+             2024-08-27 02:39:15 10.0.0.1 GET / 200 Mozilla/5.0+(X11;+Linux) -"
+        );
+
+        let second = formatter.transform(request("/about"), None).unwrap();
+        assert_eq!(
+            second.message,
+            "2024-08-27 02:39:15 10.0.0.1 GET /about 200 Mozilla/5.0+(X11;+Linux) -"
+        );
+
+        assert!(w3c_extended_header(&formatter).starts_with(
+            "#Version: 1.0\n#Fields: date time c-ip cs-method cs-uri-stem sc-status cs(User-Agent) cs(Referer)\n#Date: "
+        ));
+    }
+}
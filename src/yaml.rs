@@ -0,0 +1,81 @@
+use crate::json::JsonOptions;
+use crate::{Format, FormatOptions, LogInfo};
+use serde_json::Value;
+
+/// Renders the log info as a YAML document, with the same keys and key options
+/// as `json()`. Each document starts with `---` so records can be written one
+/// after another as a YAML stream.
+pub fn yaml() -> Format {
+    Format::new(|info: LogInfo, opts: FormatOptions| {
+        let options = JsonOptions::from(&opts);
+        let log_object = Value::Object(options.build_object(&info));
+
+        let body = serde_norway::to_string(&log_object).ok()?;
+
+        let document_start = opts
+            .as_ref()
+            .and_then(|o| o.get("document_start"))
+            .map(String::as_str)
+            != Some("false");
+
+        let document = if document_start {
+            format!("---\n{}", body.trim_end())
+        } else {
+            body.trim_end().to_string()
+        };
+
+        Some(LogInfo {
+            level: info.level,
+            message: document,
+            meta: info.meta,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_yaml_formatter() {
+        let formatter = yaml();
+
+        let info = LogInfo::new("info", "Config loaded")
+            .add_meta("ports", json!([80, 443]))
+            .add_meta("tls", json!({"enabled": true, "cert": null}))
+            .add_meta("note", "multi\nline");
+
+        let result = formatter.transform(info, None).unwrap();
+        println!("{}", result.message);
+
+        assert_eq!(
+            result.message,
+            "---\nlevel: info\nmessage: Config loaded\nnote: |-\n  multi\n  line\nports:\n- 80\n- 443\ntls:\n  cert: null\n  enabled: true"
+        );
+    }
+
+    #[test]
+    fn test_yaml_stream() {
+        let formatter = yaml().with_option("meta_key", "meta");
+
+        let stream: String = ["first", "second"]
+            .into_iter()
+            .map(|m| {
+                let info = LogInfo::new("info", m).add_meta("n", 1);
+                formatter.transform(info, None).unwrap().message + "\n"
+            })
+            .collect();
+
+        let documents: Vec<Value> = serde_norway::Deserializer::from_str(&stream)
+            .map(|doc| serde::Deserialize::deserialize(doc).unwrap())
+            .collect();
+        assert_eq!(
+            documents,
+            vec![
+                json!({"level": "info", "message": "first", "meta": {"n": 1}}),
+                json!({"level": "info", "message": "second", "meta": {"n": 1}}),
+            ]
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use logform::{access_log, json, logfmt, parse, simple, LogInfo};
+use proptest::prelude::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+fn level() -> impl Strategy<Value = String> {
+    "[a-z]{1,8}"
+}
+
+fn is_core_key(key: &str) -> bool {
+    matches!(key, "level" | "message" | "msg" | "splat" | "padding")
+}
+
+fn meta_key() -> impl Strategy<Value = String> {
+    ".{1,8}".prop_filter("core key", |k| !is_core_key(k))
+}
+
+/// Keys logfmt writes as they are: no spaces, `=`, quotes or control characters.
+fn logfmt_key() -> impl Strategy<Value = String> {
+    "[^ =\"\\p{Cc}]{1,8}".prop_filter("core key", |k| !is_core_key(k))
+}
+
+/// Any JSON value, with integers and floats kept apart.
+fn json_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>()
+            .prop_filter("finite", |f| f.is_finite())
+            .prop_map(Value::from),
+        ".*".prop_map(Value::from),
+    ];
+    leaf.prop_recursive(3, 16, 4, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+            prop::collection::btree_map("[a-z]{1,4}", inner, 0..4)
+                .prop_map(|m| Value::Object(m.into_iter().collect())),
+        ]
+    })
+}
+
+/// Any string, numbers, booleans, null, arrays and objects.
+fn logfmt_value() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<f64>()
+            .prop_filter("finite", |f| f.is_finite())
+            .prop_map(Value::from),
+        ".*".prop_map(Value::from),
+        "[0-9a-z\\[\\]{}\":, .-]{0,8}".prop_map(Value::from),
+        prop::collection::vec(any::<i32>(), 0..4).prop_map(|v| json!(v)),
+        prop::collection::vec(".*", 0..4).prop_map(|v| json!(v)),
+        prop::collection::btree_map(".{0,4}", ".*", 0..4).prop_map(|m| json!(m)),
+    ]
+}
+
+fn record(
+    message: impl Strategy<Value = String>,
+    value: impl Strategy<Value = Value>,
+) -> impl Strategy<Value = LogInfo> {
+    record_with_keys(message, meta_key(), value)
+}
+
+fn record_with_keys(
+    message: impl Strategy<Value = String>,
+    key: impl Strategy<Value = String>,
+    value: impl Strategy<Value = Value>,
+) -> impl Strategy<Value = LogInfo> {
+    (
+        level(),
+        message,
+        prop::collection::hash_map(key, value, 0..5),
+    )
+        .prop_map(|(level, message, meta)| {
+            let mut info = LogInfo::new(&level, &message);
+            info.meta = meta;
+            info
+        })
+}
+
+fn assert_same(parsed: &LogInfo, original: &LogInfo) {
+    assert_eq!(parsed.level, original.level);
+    assert_eq!(parsed.message, original.message);
+    assert_eq!(parsed.meta, original.meta);
+}
+
+/// Logfmt values are text, so only their text is expected back: quoted values are
+/// read as strings, whatever they were written from.
+fn text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn assert_same_text(parsed: &LogInfo, original: &LogInfo) {
+    assert_eq!(parsed.level, original.level);
+    assert_eq!(parsed.message, original.message);
+    let texts = |info: &LogInfo| -> HashMap<String, String> {
+        info.meta
+            .iter()
+            .map(|(k, v)| (k.clone(), text(v)))
+            .collect()
+    };
+    assert_eq!(texts(parsed), texts(original));
+}
+
+proptest! {
+    #[test]
+    fn json_lines_round_trip(info in record(".*", json_value())) {
+        let line = json().transform(info.clone(), None).unwrap().message;
+        assert_same(&parse::json(&line).unwrap(), &info);
+    }
+
+    #[test]
+    fn logfmt_round_trip(info in record_with_keys(".*", logfmt_key(), logfmt_value())) {
+        let line = logfmt().transform(info.clone(), None).unwrap().message;
+        assert_same_text(&parse::logfmt(&line).unwrap(), &info);
+    }
+
+    #[test]
+    fn logfmt_sanitizes_keys(info in record(".*", logfmt_value())) {
+        let line = logfmt().transform(info.clone(), None).unwrap().message;
+        let parsed = parse::logfmt(&line).unwrap();
+        prop_assert_eq!(&parsed.message, &info.message);
+        for (key, value) in &parsed.meta {
+            prop_assert!(!key.contains([' ', '=', '"']) && !key.chars().any(char::is_control));
+            if let Some(original) = info.meta.get(key) {
+                prop_assert_eq!(text(value), text(original));
+            }
+        }
+    }
+
+    #[test]
+    fn simple_round_trip(info in record("[^{}]*", json_value())) {
+        let line = simple().transform(info.clone(), None).unwrap().message;
+        assert_same(&parse::simple(&line).unwrap(), &info);
+    }
+
+    #[test]
+    fn access_log_round_trip(
+        remote_addr in "[0-9]{1,3}(\\.[0-9]{1,3}){3}",
+        method in "GET|POST|PUT|DELETE",
+        path in "/[a-z0-9/?=&\"%-]*",
+        status in 100u64..600,
+        bytes in any::<u32>(),
+        seconds in 0i64..4_000_000_000,
+        user_agent in "[a-zA-Z0-9/. ();\"]{1,30}",
+        combined in any::<bool>(),
+    ) {
+        let timestamp = chrono::DateTime::from_timestamp(seconds, 0).unwrap().to_rfc3339();
+        let mut meta = HashMap::new();
+        meta.insert("remote_addr".to_string(), Value::from(remote_addr));
+        meta.insert("timestamp".to_string(), Value::from(timestamp));
+        meta.insert("method".to_string(), Value::from(method));
+        meta.insert("path".to_string(), Value::from(path));
+        meta.insert("protocol".to_string(), Value::from("HTTP/1.1"));
+        meta.insert("status".to_string(), Value::from(status));
+        meta.insert("bytes".to_string(), Value::from(bytes));
+        if combined {
+            meta.insert("user_agent".to_string(), Value::from(user_agent));
+        }
+        let mut info = LogInfo::new("info", "request handled");
+        info.meta = meta.clone();
+
+        let format = access_log().with_option("format", if combined { "combined" } else { "common" });
+        let line = format.transform(info, None).unwrap().message;
+
+        let parsed = parse::clf(&line).unwrap();
+        prop_assert_eq!(&parsed.meta, &meta);
+        prop_assert_eq!(format.transform(parsed, None).unwrap().message, line);
+    }
+
+    #[test]
+    fn syslog_round_trip(
+        facility in 0u8..24,
+        severity in 0u8..8,
+        hostname in "[a-z][a-z0-9.-]{0,20}",
+        app_name in "[a-z][a-z0-9]{0,10}",
+        procid in 1u32..100_000,
+        message in "[^\u{feff}]*",
+    ) {
+        let levels = ["emerg", "alert", "crit", "error", "warning", "notice", "info", "debug"];
+        let pri = facility * 8 + severity;
+
+        let line = format!(
+            "<{}>1 2024-08-27T02:39:15.003Z {} {} {} - - {}",
+            pri, hostname, app_name, procid, message
+        );
+        let parsed = parse::syslog(&line).unwrap();
+        prop_assert_eq!(parsed.level.as_str(), levels[severity as usize]);
+        prop_assert_eq!(&parsed.message, &message);
+        prop_assert_eq!(&parsed.meta["hostname"], &Value::from(hostname.clone()));
+        prop_assert_eq!(&parsed.meta["procid"], &Value::from(procid.to_string()));
+
+        let line = format!(
+            "<{}>Aug  7 02:39:15 {} {}[{}]: {}",
+            pri, hostname, app_name, procid, message
+        );
+        let parsed = parse::syslog(&line).unwrap();
+        prop_assert_eq!(&parsed.meta["facility"], &Value::from(facility));
+        prop_assert_eq!(&parsed.meta["app_name"], &Value::from(app_name));
+        prop_assert_eq!(&parsed.message, &message);
+    }
+}